@@ -1,4 +1,5 @@
 use crate::client::PolishApiClient;
+use crate::profile::Endpoint;
 use crate::types::{
     Result, RequestHeaders,
     GetAccountsRequest, GetAccountsResponse,
@@ -6,7 +7,7 @@ use crate::types::{
     GetTransactionsRequest, GetTransactionsResponse,
     GetTransactionDetailRequest, GetTransactionDetailResponse,
     GetHoldsRequest, GetHoldsResponse,
-    DeleteConsentRequest, DeleteConsentResponse,
+    DeleteConsentRequest,
 };
 
 /// Account Information Service implementation
@@ -30,32 +31,19 @@ impl<'a> AccountService<'a> {
     /// List of accounts
     pub async fn get_accounts(
         &self,
-        request: GetAccountsRequest,
+        mut request: GetAccountsRequest,
         headers: RequestHeaders,
     ) -> Result<GetAccountsResponse> {
-        let payload = serde_json::to_string(&request)?;
-        let signature = self.client.sign_payload(&payload).await?;
-
         let mut request_headers = headers;
-        request_headers.x_jws_signature = signature;
-
-        let builder = self.client.request_builder(
-            reqwest::Method::POST,
-            "/v3_0.1/accounts/v3_0.1/getAccounts",
-        )?;
-
-        let builder = self.client.add_auth_headers(builder, &request_headers);
+        self.client.prepare_request(&mut request, &mut request_headers);
 
-        let response = builder
-            .body(payload)
-            .send()
-            .await?;
+        let response = self.client.execute(Endpoint::GetAccounts, &request, request_headers).await?;
 
         if response.status().is_success() {
-            let accounts_response: GetAccountsResponse = response.json().await?;
+            let accounts_response: GetAccountsResponse = self.client.parse_response(response).await?;
             Ok(accounts_response)
         } else {
-            let error_text = response.text().await?;
+            let error_text = self.client.error_text(response).await?;
             Err(crate::types::PolishApiError::Api {
                 code: "ACCOUNTS_ERROR".to_string(),
                 message: error_text,
@@ -73,32 +61,19 @@ impl<'a> AccountService<'a> {
     /// Account details
     pub async fn get_account(
         &self,
-        request: GetAccountRequest,
+        mut request: GetAccountRequest,
         headers: RequestHeaders,
     ) -> Result<GetAccountResponse> {
-        let payload = serde_json::to_string(&request)?;
-        let signature = self.client.sign_payload(&payload).await?;
-
         let mut request_headers = headers;
-        request_headers.x_jws_signature = signature;
-
-        let builder = self.client.request_builder(
-            reqwest::Method::POST,
-            "/v3_0.1/accounts/v3_0.1/getAccount",
-        )?;
+        self.client.prepare_request(&mut request, &mut request_headers);
 
-        let builder = self.client.add_auth_headers(builder, &request_headers);
-
-        let response = builder
-            .body(payload)
-            .send()
-            .await?;
+        let response = self.client.execute(Endpoint::GetAccount, &request, request_headers).await?;
 
         if response.status().is_success() {
-            let account_response: GetAccountResponse = response.json().await?;
+            let account_response: GetAccountResponse = self.client.parse_response(response).await?;
             Ok(account_response)
         } else {
-            let error_text = response.text().await?;
+            let error_text = self.client.error_text(response).await?;
             Err(crate::types::PolishApiError::Api {
                 code: "ACCOUNT_ERROR".to_string(),
                 message: error_text,
@@ -119,7 +94,7 @@ impl<'a> AccountService<'a> {
         request: GetTransactionsRequest,
         headers: RequestHeaders,
     ) -> Result<GetTransactionsResponse> {
-        self.get_transactions_by_status(request, headers, "getTransactionsDone").await
+        self.get_transactions_by_status(request, headers, Endpoint::GetTransactionsDone).await
     }
 
     /// Get pending transactions
@@ -135,7 +110,7 @@ impl<'a> AccountService<'a> {
         request: GetTransactionsRequest,
         headers: RequestHeaders,
     ) -> Result<GetTransactionsResponse> {
-        self.get_transactions_by_status(request, headers, "getTransactionsPending").await
+        self.get_transactions_by_status(request, headers, Endpoint::GetTransactionsPending).await
     }
 
     /// Get rejected transactions
@@ -151,7 +126,7 @@ impl<'a> AccountService<'a> {
         request: GetTransactionsRequest,
         headers: RequestHeaders,
     ) -> Result<GetTransactionsResponse> {
-        self.get_transactions_by_status(request, headers, "getTransactionsRejected").await
+        self.get_transactions_by_status(request, headers, Endpoint::GetTransactionsRejected).await
     }
 
     /// Get cancelled transactions
@@ -167,7 +142,7 @@ impl<'a> AccountService<'a> {
         request: GetTransactionsRequest,
         headers: RequestHeaders,
     ) -> Result<GetTransactionsResponse> {
-        self.get_transactions_by_status(request, headers, "getTransactionsCancelled").await
+        self.get_transactions_by_status(request, headers, Endpoint::GetTransactionsCancelled).await
     }
 
     /// Get scheduled transactions
@@ -183,7 +158,7 @@ impl<'a> AccountService<'a> {
         request: GetTransactionsRequest,
         headers: RequestHeaders,
     ) -> Result<GetTransactionsResponse> {
-        self.get_transactions_by_status(request, headers, "getTransactionsScheduled").await
+        self.get_transactions_by_status(request, headers, Endpoint::GetTransactionsScheduled).await
     }
 
     /// Get transaction details
@@ -196,32 +171,19 @@ impl<'a> AccountService<'a> {
     /// Transaction details
     pub async fn get_transaction_detail(
         &self,
-        request: GetTransactionDetailRequest,
+        mut request: GetTransactionDetailRequest,
         headers: RequestHeaders,
     ) -> Result<GetTransactionDetailResponse> {
-        let payload = serde_json::to_string(&request)?;
-        let signature = self.client.sign_payload(&payload).await?;
-
         let mut request_headers = headers;
-        request_headers.x_jws_signature = signature;
-
-        let builder = self.client.request_builder(
-            reqwest::Method::POST,
-            "/v3_0.1/accounts/v3_0.1/getTransactionDetail",
-        )?;
+        self.client.prepare_request(&mut request, &mut request_headers);
 
-        let builder = self.client.add_auth_headers(builder, &request_headers);
-
-        let response = builder
-            .body(payload)
-            .send()
-            .await?;
+        let response = self.client.execute(Endpoint::GetTransactionDetail, &request, request_headers).await?;
 
         if response.status().is_success() {
-            let transaction_response: GetTransactionDetailResponse = response.json().await?;
+            let transaction_response: GetTransactionDetailResponse = self.client.parse_response(response).await?;
             Ok(transaction_response)
         } else {
-            let error_text = response.text().await?;
+            let error_text = self.client.error_text(response).await?;
             Err(crate::types::PolishApiError::Api {
                 code: "TRANSACTION_DETAIL_ERROR".to_string(),
                 message: error_text,
@@ -239,32 +201,19 @@ impl<'a> AccountService<'a> {
     /// List of account holds
     pub async fn get_holds(
         &self,
-        request: GetHoldsRequest,
+        mut request: GetHoldsRequest,
         headers: RequestHeaders,
     ) -> Result<GetHoldsResponse> {
-        let payload = serde_json::to_string(&request)?;
-        let signature = self.client.sign_payload(&payload).await?;
-
         let mut request_headers = headers;
-        request_headers.x_jws_signature = signature;
+        self.client.prepare_request(&mut request, &mut request_headers);
 
-        let builder = self.client.request_builder(
-            reqwest::Method::POST,
-            "/v3_0.1/accounts/v3_0.1/getHolds",
-        )?;
-
-        let builder = self.client.add_auth_headers(builder, &request_headers);
-
-        let response = builder
-            .body(payload)
-            .send()
-            .await?;
+        let response = self.client.execute(Endpoint::GetHolds, &request, request_headers).await?;
 
         if response.status().is_success() {
-            let holds_response: GetHoldsResponse = response.json().await?;
+            let holds_response: GetHoldsResponse = self.client.parse_response(response).await?;
             Ok(holds_response)
         } else {
-            let error_text = response.text().await?;
+            let error_text = self.client.error_text(response).await?;
             Err(crate::types::PolishApiError::Api {
                 code: "HOLDS_ERROR".to_string(),
                 message: error_text,
@@ -279,35 +228,21 @@ impl<'a> AccountService<'a> {
     /// * `headers` - Request headers including authentication
     ///
     /// # Returns
-    /// Consent deletion confirmation
+    /// Success (204) or error
     pub async fn delete_consent(
         &self,
-        request: DeleteConsentRequest,
+        mut request: DeleteConsentRequest,
         headers: RequestHeaders,
-    ) -> Result<DeleteConsentResponse> {
-        let payload = serde_json::to_string(&request)?;
-        let signature = self.client.sign_payload(&payload).await?;
-
+    ) -> Result<()> {
         let mut request_headers = headers;
-        request_headers.x_jws_signature = signature;
-
-        let builder = self.client.request_builder(
-            reqwest::Method::POST,
-            "/v3_0.1/accounts/v3_0.1/deleteConsent",
-        )?;
-
-        let builder = self.client.add_auth_headers(builder, &request_headers);
+        self.client.prepare_request(&mut request, &mut request_headers);
 
-        let response = builder
-            .body(payload)
-            .send()
-            .await?;
+        let response = self.client.execute(Endpoint::DeleteConsent, &request, request_headers).await?;
 
         if response.status().is_success() {
-            let consent_response: DeleteConsentResponse = response.json().await?;
-            Ok(consent_response)
+            Ok(())
         } else {
-            let error_text = response.text().await?;
+            let error_text = self.client.error_text(response).await?;
             Err(crate::types::PolishApiError::Api {
                 code: "DELETE_CONSENT_ERROR".to_string(),
                 message: error_text,
@@ -318,31 +253,20 @@ impl<'a> AccountService<'a> {
     /// Helper method to get transactions by status
     async fn get_transactions_by_status(
         &self,
-        request: GetTransactionsRequest,
+        mut request: GetTransactionsRequest,
         headers: RequestHeaders,
-        endpoint: &str,
+        endpoint: Endpoint,
     ) -> Result<GetTransactionsResponse> {
-        let payload = serde_json::to_string(&request)?;
-        let signature = self.client.sign_payload(&payload).await?;
-
         let mut request_headers = headers;
-        request_headers.x_jws_signature = signature;
-
-        let path = format!("/v3_0.1/accounts/v3_0.1/{}", endpoint);
-        let builder = self.client.request_builder(reqwest::Method::POST, &path)?;
-
-        let builder = self.client.add_auth_headers(builder, &request_headers);
+        self.client.prepare_request(&mut request, &mut request_headers);
 
-        let response = builder
-            .body(payload)
-            .send()
-            .await?;
+        let response = self.client.execute(endpoint, &request, request_headers).await?;
 
         if response.status().is_success() {
-            let transactions_response: GetTransactionsResponse = response.json().await?;
+            let transactions_response: GetTransactionsResponse = self.client.parse_response(response).await?;
             Ok(transactions_response)
         } else {
-            let error_text = response.text().await?;
+            let error_text = self.client.error_text(response).await?;
             Err(crate::types::PolishApiError::Api {
                 code: "TRANSACTIONS_ERROR".to_string(),
                 message: error_text,
@@ -1,11 +1,23 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64URL;
+use base64::Engine as _;
+use chrono::{DateTime, Utc};
+use ring::digest::{digest, SHA256};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+
 use crate::client::PolishApiClient;
+use crate::profile::Endpoint;
+use crate::registration::{sign_client_assertion, sign_software_statement, SOFTWARE_STATEMENT_LIFETIME};
 use crate::types::{
-    Result, RequestHeaders,
+    PolishApiError, Result, RequestHeaders,
     AuthorizeRequest, AuthorizeResponse,
     EatCodeRequest, TokenRequest, TokenResponse,
-    RegisterRequest, RegisterResponse,
+    ClientAuthMethod, ClientMetadata, RegisterRequest, RegisterResponse, ScopeDetails,
 };
 
+/// `client_assertion_type` of a `private_key_jwt` assertion (RFC 7523)
+const CLIENT_ASSERTION_TYPE: &str = "urn:ietf:params:oauth:client-assertion-type:jwt-bearer";
+
 /// Authorization Service implementation
 pub struct AuthService<'a> {
     client: &'a PolishApiClient,
@@ -19,6 +31,8 @@ impl<'a> AuthService<'a> {
 
     /// Request OAuth2 authorization code
     ///
+    /// `scope_details` is validated before the request is sent.
+    ///
     /// # Arguments
     /// * `request` - Authorization request parameters
     /// * `headers` - Request headers including authentication
@@ -27,32 +41,19 @@ impl<'a> AuthService<'a> {
     /// Authorization response with authorization URL
     pub async fn authorize(
         &self,
-        request: AuthorizeRequest,
+        mut request: AuthorizeRequest,
         headers: RequestHeaders,
     ) -> Result<AuthorizeResponse> {
-        let payload = serde_json::to_string(&request)?;
-        let signature = self.client.sign_payload(&payload).await?;
-
         let mut request_headers = headers;
-        request_headers.x_jws_signature = signature;
+        self.client.prepare_request(&mut request, &mut request_headers);
 
-        let builder = self.client.request_builder(
-            reqwest::Method::POST,
-            "/v3_0.1/auth/v3_0.1/authorize",
-        )?;
-
-        let builder = self.client.add_auth_headers(builder, &request_headers);
-
-        let response = builder
-            .body(payload)
-            .send()
-            .await?;
+        let response = self.client.execute(Endpoint::Authorize, &request, request_headers).await?;
 
         if response.status().is_success() {
-            let auth_response: AuthorizeResponse = response.json().await?;
+            let auth_response: AuthorizeResponse = self.client.parse_response(response).await?;
             Ok(auth_response)
         } else {
-            let error_text = response.text().await?;
+            let error_text = self.client.error_text(response).await?;
             Err(crate::types::PolishApiError::Api {
                 code: "AUTH_ERROR".to_string(),
                 message: error_text,
@@ -70,31 +71,18 @@ impl<'a> AuthService<'a> {
     /// Success (204) or error
     pub async fn authorize_ext(
         &self,
-        request: EatCodeRequest,
+        mut request: EatCodeRequest,
         headers: RequestHeaders,
     ) -> Result<()> {
-        let payload = serde_json::to_string(&request)?;
-        let signature = self.client.sign_payload(&payload).await?;
-
         let mut request_headers = headers;
-        request_headers.x_jws_signature = signature;
+        self.client.prepare_request(&mut request, &mut request_headers);
 
-        let builder = self.client.request_builder(
-            reqwest::Method::POST,
-            "/v3_0.1/auth/v3_0.1/authorizeExt",
-        )?;
-
-        let builder = self.client.add_auth_headers(builder, &request_headers);
-
-        let response = builder
-            .body(payload)
-            .send()
-            .await?;
+        let response = self.client.execute(Endpoint::AuthorizeExt, &request, request_headers).await?;
 
         if response.status().is_success() {
             Ok(())
         } else {
-            let error_text = response.text().await?;
+            let error_text = self.client.error_text(response).await?;
             Err(crate::types::PolishApiError::Api {
                 code: "AUTH_EXT_ERROR".to_string(),
                 message: error_text,
@@ -108,36 +96,29 @@ impl<'a> AuthService<'a> {
     /// * `request` - Token request parameters
     /// * `headers` - Request headers including authentication
     ///
+    /// The fields required by the grant are checked before the request is sent,
+    /// and the client authenticates with the method selected in
+    /// [`crate::Config::client_auth_method`]. Pass the response to
+    /// [`crate::TokenManager::store_response`] to have its token managed.
+    ///
     /// # Returns
     /// Token response with access token
     pub async fn token(
         &self,
-        request: TokenRequest,
+        mut request: TokenRequest,
         headers: RequestHeaders,
     ) -> Result<TokenResponse> {
-        let payload = serde_json::to_string(&request)?;
-        let signature = self.client.sign_payload(&payload).await?;
-
+        self.authenticate_client(&mut request).await?;
         let mut request_headers = headers;
-        request_headers.x_jws_signature = signature;
+        self.client.prepare_request(&mut request, &mut request_headers);
 
-        let builder = self.client.request_builder(
-            reqwest::Method::POST,
-            "/v3_0.1/auth/v3_0.1/token",
-        )?;
-
-        let builder = self.client.add_auth_headers(builder, &request_headers);
-
-        let response = builder
-            .body(payload)
-            .send()
-            .await?;
+        let response = self.client.execute(Endpoint::Token, &request, request_headers).await?;
 
         if response.status().is_success() {
-            let token_response: TokenResponse = response.json().await?;
+            let token_response: TokenResponse = self.client.parse_response(response).await?;
             Ok(token_response)
         } else {
-            let error_text = response.text().await?;
+            let error_text = self.client.error_text(response).await?;
             Err(crate::types::PolishApiError::Api {
                 code: "TOKEN_ERROR".to_string(),
                 message: error_text,
@@ -145,6 +126,133 @@ impl<'a> AuthService<'a> {
         }
     }
 
+    /// Register the client with a software statement signed by the JWS signer
+    ///
+    /// The statement is issued by the configured TPP ID (or the metadata's
+    /// `software_id`). Pass the response to
+    /// [`PolishApiClient::apply_registration`] to use the issued credentials.
+    ///
+    /// # Arguments
+    /// * `metadata` - Client metadata, sent both in the clear and in the statement
+    /// * `headers` - Request headers
+    ///
+    /// # Returns
+    /// Registration response with client credentials
+    pub async fn register_with_software_statement(
+        &self,
+        metadata: ClientMetadata,
+        headers: RequestHeaders,
+    ) -> Result<RegisterResponse> {
+        let signer = self
+            .client
+            .jws_signer()
+            .ok_or_else(|| PolishApiError::Config("JWS signer not configured".to_string()))?;
+        let issuer = self
+            .client
+            .config()
+            .tpp_id
+            .as_deref()
+            .or(metadata.software_id.as_deref())
+            .ok_or_else(|| PolishApiError::Config("TPP ID or software_id required as statement issuer".to_string()))?;
+
+        let software_statement =
+            sign_software_statement(signer, issuer, &metadata, SOFTWARE_STATEMENT_LIFETIME).await?;
+        let request = RegisterRequest {
+            request_header: Default::default(),
+            metadata,
+            software_statement: Some(software_statement),
+        };
+        self.register(request, headers).await
+    }
+
+    /// Exchange an access token for one with a different scope
+    ///
+    /// Reuses one PSU login across contexts, e.g. trading an `ais-accounts`
+    /// token for an `ais` token covering the transaction history of an account
+    /// picked from the list.
+    ///
+    /// # Arguments
+    /// * `access_token` - Token to exchange
+    /// * `scope_details` - Scope of the new token
+    /// * `headers` - Request headers
+    ///
+    /// # Returns
+    /// Token response with the new access token
+    pub async fn exchange_token(
+        &self,
+        access_token: impl Into<String>,
+        scope_details: ScopeDetails,
+        headers: RequestHeaders,
+    ) -> Result<TokenResponse> {
+        let request = TokenRequest::exchange_token(&self.client.config().client_id, access_token, scope_details);
+        self.token(request, headers).await
+    }
+
+    /// Drive a decoupled authorization started with [`AuthService::authorize_ext`]
+    pub fn decoupled(&self) -> crate::decoupled::DecoupledAuthorization<'a> {
+        crate::decoupled::DecoupledAuthorization::new(self.client)
+    }
+
+    /// Exchange the authorization code returned on the redirect for a token
+    ///
+    /// The `state` from the redirect is checked against the session before the
+    /// code is sent together with the session's PKCE code verifier.
+    ///
+    /// # Arguments
+    /// * `session` - Session started before the authorize call
+    /// * `state` - `state` query parameter of the redirect
+    /// * `code` - `code` query parameter of the redirect
+    /// * `headers` - Request headers
+    ///
+    /// # Returns
+    /// Token response with access token
+    pub async fn exchange_code(
+        &self,
+        session: &AuthorizationSession,
+        state: &str,
+        code: impl Into<String>,
+        headers: RequestHeaders,
+    ) -> Result<TokenResponse> {
+        session.validate_state(state)?;
+        let request = session.token_request(&self.client.config().client_id, code);
+        self.token(request, headers).await
+    }
+
+    /// Add the client credentials required by the configured authentication method
+    async fn authenticate_client(&self, request: &mut TokenRequest) -> Result<()> {
+        let config = self.client.config();
+        match config.client_auth_method {
+            ClientAuthMethod::ClientSecretPost => {
+                if request.client_secret.is_none() {
+                    request.client_secret = config.client_secret.clone();
+                }
+            }
+            ClientAuthMethod::PrivateKeyJwt => {
+                let signer = self
+                    .client
+                    .jws_signer()
+                    .ok_or_else(|| PolishApiError::Config("private_key_jwt requires a JWS signer".to_string()))?;
+                let audience = config
+                    .base_url
+                    .join(&config.endpoint_path(Endpoint::Token)?)
+                    .map_err(|e| PolishApiError::Config(format!("Invalid path: {}", e)))?;
+                request.client_secret = None;
+                request.client_assertion_type = Some(CLIENT_ASSERTION_TYPE.to_string());
+                request.client_assertion =
+                    Some(sign_client_assertion(signer, &request.client_id, audience.as_str()).await?);
+            }
+            ClientAuthMethod::TlsClientAuth => {
+                if config.client_identity.is_none() {
+                    return Err(PolishApiError::Config(
+                        "tls_client_auth requires a client identity".to_string(),
+                    ));
+                }
+                request.client_secret = None;
+            }
+        }
+        Ok(())
+    }
+
     /// Register a new client
     ///
     /// # Arguments
@@ -155,32 +263,19 @@ impl<'a> AuthService<'a> {
     /// Registration response with client credentials
     pub async fn register(
         &self,
-        request: RegisterRequest,
+        mut request: RegisterRequest,
         headers: RequestHeaders,
     ) -> Result<RegisterResponse> {
-        let payload = serde_json::to_string(&request)?;
-        let signature = self.client.sign_payload(&payload).await?;
-
         let mut request_headers = headers;
-        request_headers.x_jws_signature = signature;
-
-        let builder = self.client.request_builder(
-            reqwest::Method::POST,
-            "/v3_0.1/auth/v3_0.1/register",
-        )?;
+        self.client.prepare_request(&mut request, &mut request_headers);
 
-        let builder = self.client.add_auth_headers(builder, &request_headers);
-
-        let response = builder
-            .body(payload)
-            .send()
-            .await?;
+        let response = self.client.execute(Endpoint::Register, &request, request_headers).await?;
 
         if response.status().is_success() {
-            let register_response: RegisterResponse = response.json().await?;
+            let register_response: RegisterResponse = self.client.parse_response(response).await?;
             Ok(register_response)
         } else {
-            let error_text = response.text().await?;
+            let error_text = self.client.error_text(response).await?;
             Err(crate::types::PolishApiError::Api {
                 code: "REGISTER_ERROR".to_string(),
                 message: error_text,
@@ -189,3 +284,288 @@ impl<'a> AuthService<'a> {
     }
 }
 
+
+/// PKCE verifier and `state` of one authorization code flow
+///
+/// Start a session before redirecting the PSU, keep it (it is serializable)
+/// in the PSU's session, and pass it to [`AuthService::exchange_code`] when
+/// the redirect comes back.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorizationSession {
+    pub redirect_uri: String,
+    pub code_verifier: String,
+    pub code_challenge: String,
+    pub state: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AuthorizationSession {
+    /// Start a session with a random code verifier and `state`
+    pub fn new(redirect_uri: impl Into<String>) -> Result<Self> {
+        let rng = SystemRandom::new();
+        let code_verifier = random_token(&rng, 32)?;
+        let code_challenge = BASE64URL.encode(digest(&SHA256, code_verifier.as_bytes()).as_ref());
+
+        Ok(Self {
+            redirect_uri: redirect_uri.into(),
+            code_verifier,
+            code_challenge,
+            state: random_token(&rng, 16)?,
+            created_at: Utc::now(),
+        })
+    }
+
+    /// Build the authorize request carrying the S256 code challenge and `state`
+    pub fn authorize_request(&self, client_id: impl Into<String>, scope: impl Into<String>) -> AuthorizeRequest {
+        AuthorizeRequest {
+            request_header: Default::default(),
+            response_type: "code".to_string(),
+            client_id: client_id.into(),
+            redirect_uri: self.redirect_uri.clone(),
+            scope: scope.into(),
+            scope_details: None,
+            state: Some(self.state.clone()),
+            code_challenge: Some(self.code_challenge.clone()),
+            code_challenge_method: Some("S256".to_string()),
+        }
+    }
+
+    /// Check the `state` returned on the redirect
+    pub fn validate_state(&self, state: &str) -> Result<()> {
+        let matches = state.len() == self.state.len()
+            && state.bytes().zip(self.state.bytes()).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0;
+        if matches {
+            Ok(())
+        } else {
+            Err(PolishApiError::Authorization {
+                message: "state returned on the redirect does not match the session".to_string(),
+            })
+        }
+    }
+
+    /// Build the `authorization_code` token request carrying the code verifier
+    pub fn token_request(&self, client_id: impl Into<String>, code: impl Into<String>) -> TokenRequest {
+        TokenRequest {
+            redirect_uri: Some(self.redirect_uri.clone()),
+            code_verifier: Some(self.code_verifier.clone()),
+            ..TokenRequest::authorization_code(client_id, code)
+        }
+    }
+}
+
+/// Base64url encoding of `len` random bytes
+fn random_token(rng: &SystemRandom, len: usize) -> Result<String> {
+    let mut bytes = vec![0u8; len];
+    rng.fill(&mut bytes)
+        .map_err(|_| PolishApiError::Crypto("Failed to generate random bytes".to_string()))?;
+    Ok(BASE64URL.encode(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, token_body};
+    use crate::types::GrantType;
+
+    #[test]
+    fn test_session_values() {
+        let session = AuthorizationSession::new("https://tpp.example.com/callback").unwrap();
+
+        // RFC 7636: 43 characters from the unreserved set
+        assert_eq!(session.code_verifier.len(), 43);
+        assert!(session.code_verifier.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(
+            session.code_challenge,
+            BASE64URL.encode(digest(&SHA256, session.code_verifier.as_bytes()).as_ref())
+        );
+
+        let other = AuthorizationSession::new("https://tpp.example.com/callback").unwrap();
+        assert_ne!(session.code_verifier, other.code_verifier);
+        assert_ne!(session.state, other.state);
+    }
+
+    #[test]
+    fn test_rfc7636_challenge() {
+        // Appendix B of RFC 7636
+        let verifier = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+        assert_eq!(
+            BASE64URL.encode(digest(&SHA256, verifier.as_bytes()).as_ref()),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[test]
+    fn test_validate_state() {
+        let session = AuthorizationSession::new("https://tpp.example.com/callback").unwrap();
+        assert!(session.validate_state(&session.state.clone()).is_ok());
+        assert!(matches!(session.validate_state("forged"), Err(PolishApiError::Authorization { .. })));
+        assert!(session.validate_state("").is_err());
+    }
+
+    #[test]
+    fn test_requests_carry_session_values() {
+        let session = AuthorizationSession::new("https://tpp.example.com/callback").unwrap();
+
+        let authorize = session.authorize_request("client", "ais");
+        assert_eq!(authorize.state.as_ref(), Some(&session.state));
+        assert_eq!(authorize.code_challenge.as_ref(), Some(&session.code_challenge));
+        assert_eq!(authorize.code_challenge_method.as_deref(), Some("S256"));
+
+        let token = session.token_request("client", "auth-code");
+        assert_eq!(token.code.as_deref(), Some("auth-code"));
+        assert_eq!(token.code_verifier.as_ref(), Some(&session.code_verifier));
+        assert_eq!(token.redirect_uri.as_ref(), Some(&session.redirect_uri));
+    }
+
+    #[tokio::test]
+    async fn test_exchange_token() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v3_0.1/auth/v3_0.1/token")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "grant_type": "exchange_token",
+                "exchange_token": "accounts-token",
+                "scope": "ais",
+                "scope_details": { "scopeGroupType": "ais", "consentId": "consent-2" }
+            })))
+            .with_body(token_body("ais-token"))
+            .create_async()
+            .await;
+        let client = test_support::client(test_support::config(&server)).await;
+
+        let scope_details = ScopeDetails::builder(crate::types::ScopeGroupType::Ais, "consent-2")
+            .valid_for(chrono::Duration::days(1))
+            .privilege(
+                crate::types::PrivilegeList::for_account("PL61109010140000071219812874")
+                    .with_get_transactions_done(crate::types::ScopeUsageLimit::Multiple, 90),
+            )
+            .build()
+            .unwrap();
+        let response = client
+            .auth()
+            .exchange_token("accounts-token", scope_details, RequestHeaders::default())
+            .await
+            .unwrap();
+
+        assert_eq!(response.access_token, "ais-token");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_register_with_software_statement() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v3_0.1/auth/v3_0.1/register")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::PartialJson(serde_json::json!({
+                    "client_name": "Example TPP",
+                    "requestHeader": { "tppId": "PSDPL-KNF-0000000000" }
+                })),
+                mockito::Matcher::Regex(r#""software_statement":"[\w-]+\.[\w-]+\.[\w-]+""#.to_string()),
+            ]))
+            .with_body(include_str!("../testdata/fixtures/as/register_response.json"))
+            .create_async()
+            .await;
+        let config = test_support::config(&server).with_tpp_id("PSDPL-KNF-0000000000");
+        let mut client = test_support::client(config).await;
+
+        let metadata = ClientMetadata {
+            client_name: "Example TPP".to_string(),
+            redirect_uris: vec!["https://tpp.example.com/redirect".to_string()],
+            token_endpoint_auth_method: "tls_client_auth".to_string(),
+            ..Default::default()
+        };
+        let response = client
+            .auth()
+            .register_with_software_statement(metadata, RequestHeaders::default())
+            .await
+            .unwrap();
+        client.apply_registration(&response);
+
+        mock.assert_async().await;
+        assert_eq!(client.config().client_id, "tpp-client-1");
+    }
+
+    #[tokio::test]
+    async fn test_token_request_is_validated() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server.mock("POST", "/v3_0.1/auth/v3_0.1/token").expect(0).create_async().await;
+        let client = PolishApiClient::new(test_support::config(&server)).await.unwrap();
+
+        for grant_type in [GrantType::AuthorizationCode, GrantType::RefreshToken, GrantType::ExchangeToken] {
+            let result = client.auth().token(TokenRequest::new(grant_type, "client"), RequestHeaders::default()).await;
+            assert!(matches!(result, Err(PolishApiError::Validation(_))));
+        }
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_exchange_code_rejects_state_mismatch() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server.mock("POST", "/v3_0.1/auth/v3_0.1/token").expect(0).create_async().await;
+        let client = PolishApiClient::new(test_support::config(&server)).await.unwrap();
+        let session = AuthorizationSession::new("https://tpp.example.com/callback").unwrap();
+
+        let result = client.auth().exchange_code(&session, "forged", "code", RequestHeaders::default()).await;
+        assert!(matches!(result, Err(PolishApiError::Authorization { .. })));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_private_key_jwt_client_assertion() {
+        let mut server = mockito::Server::new_async().await;
+        let body = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let captured = body.clone();
+        let mock = server
+            .mock("POST", "/v3_0.1/auth/v3_0.1/token")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "client_assertion_type": "urn:ietf:params:oauth:client-assertion-type:jwt-bearer"
+            })))
+            .with_body_from_request(move |request| {
+                *captured.lock().unwrap() = request.body().unwrap().clone();
+                token_body("granted").into()
+            })
+            .create_async()
+            .await;
+        let config = test_support::config(&server)
+            .with_client_secret("secret")
+            .with_client_auth_method(ClientAuthMethod::PrivateKeyJwt);
+        let client = test_support::client(config).await;
+
+        let request = TokenRequest::authorization_code("client", "auth-code");
+        client.auth().token(request, RequestHeaders::default()).await.unwrap();
+        mock.assert_async().await;
+
+        let body: serde_json::Value = serde_json::from_slice(&body.lock().unwrap()).unwrap();
+        assert!(body.get("client_secret").is_none());
+        let audience = format!("{}/v3_0.1/auth/v3_0.1/token", server.url());
+        let mut validation = jsonwebtoken::Validation::new(jsonwebtoken::Algorithm::RS256);
+        validation.set_issuer(&["client"]);
+        validation.set_audience(&[&audience]);
+        validation.sub = Some("client".to_string());
+        let claims = jsonwebtoken::decode::<serde_json::Value>(
+            body["client_assertion"].as_str().unwrap(),
+            &jsonwebtoken::DecodingKey::from_rsa_pem(include_bytes!("../testdata/rsa_public_key.pem")).unwrap(),
+            &validation,
+        )
+        .unwrap()
+        .claims;
+        assert!(claims["jti"].is_string());
+        assert_eq!(claims["exp"].as_i64().unwrap() - claims["iat"].as_i64().unwrap(), 60);
+    }
+
+    #[tokio::test]
+    async fn test_client_auth_method_requirements() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server.mock("POST", "/v3_0.1/auth/v3_0.1/token").expect(0).create_async().await;
+
+        for method in [ClientAuthMethod::PrivateKeyJwt, ClientAuthMethod::TlsClientAuth] {
+            let config = test_support::config(&server).with_client_auth_method(method);
+            let client = PolishApiClient::new(config).await.unwrap();
+            let request = TokenRequest::authorization_code("client", "auth-code");
+            let result = client.auth().token(request, RequestHeaders::default()).await;
+            assert!(matches!(result, Err(PolishApiError::Config(_))));
+        }
+        mock.assert_async().await;
+    }
+}
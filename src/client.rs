@@ -1,9 +1,28 @@
-use reqwest::{Client, RequestBuilder};
-use std::time::Duration;
+use reqwest::header::HeaderMap;
+use reqwest::{Client, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::Instrument;
 use url::Url;
 
-use crate::types::{PolishApiError, Result, RequestHeaders};
-use crate::crypto::JwsSigner;
+use chrono::Utc;
+
+use crate::types::{
+    ClientAuthMethod, JwsError, PolishApiError, PolishApiRequest, RegisterResponse, Result, RequestHeaders, ServiceRequestHeader,
+};
+use crate::types::request_header::fill;
+use crate::crypto::{sign_detached, JwsVerifier, Signer};
+use crate::middleware::{self, ApiRequest, Middleware, Next};
+#[cfg(feature = "metrics")]
+use crate::metrics::Metrics;
+use crate::profile::{AspspProfile, Endpoint};
+use crate::redaction::RedactionPolicy;
+use crate::retry::{self, RetryPolicy};
+use crate::tls::{self, ClientIdentity};
+use crate::throttle::{Throttle, ThrottledCall};
+use crate::token::TokenManager;
 
 /// Configuration for the PolishAPI client
 #[derive(Debug, Clone)]
@@ -11,8 +30,18 @@ pub struct Config {
     pub base_url: Url,
     pub client_id: String,
     pub client_secret: Option<String>,
+    pub client_auth_method: ClientAuthMethod,
+    pub tpp_id: Option<String>,
     pub timeout: Duration,
     pub user_agent: String,
+    pub client_identity: Option<ClientIdentity>,
+    pub root_certificates: Vec<Vec<u8>>,
+    /// Bank profile the endpoint paths are taken from
+    pub profile: Option<AspspProfile>,
+    pub retry_policy: RetryPolicy,
+    /// Redaction applied to request and response bodies logged at debug level;
+    /// bodies are not logged when unset
+    pub body_logging: Option<RedactionPolicy>,
 }
 
 impl Config {
@@ -25,8 +54,15 @@ impl Config {
             base_url,
             client_id: String::new(),
             client_secret: None,
+            client_auth_method: ClientAuthMethod::default(),
+            tpp_id: None,
             timeout: Duration::from_secs(30),
             user_agent: format!("polishapi-rust/{}", env!("CARGO_PKG_VERSION")),
+            client_identity: None,
+            root_certificates: Vec::new(),
+            profile: None,
+            retry_policy: RetryPolicy::default(),
+            body_logging: None,
         })
     }
 
@@ -42,6 +78,26 @@ impl Config {
         self
     }
 
+    /// Set how the client authenticates at the token endpoint
+    pub fn with_client_auth_method(mut self, method: ClientAuthMethod) -> Self {
+        self.client_auth_method = method;
+        self
+    }
+
+    /// Set the TPP identifier sent in the `requestHeader`
+    pub fn with_tpp_id(mut self, tpp_id: impl Into<String>) -> Self {
+        self.tpp_id = Some(tpp_id.into());
+        self
+    }
+
+    /// Use the client credentials issued by dynamic client registration
+    pub fn apply_registration(&mut self, registration: &RegisterResponse) {
+        self.client_id = registration.client_id.clone();
+        if registration.client_secret.is_some() {
+            self.client_secret = registration.client_secret.clone();
+        }
+    }
+
     /// Set the request timeout
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
         self.timeout = timeout;
@@ -53,21 +109,76 @@ impl Config {
         self.user_agent = user_agent.into();
         self
     }
+
+    /// Set the client certificate used for mutual TLS (QWAC)
+    pub fn with_client_identity(mut self, identity: ClientIdentity) -> Self {
+        self.client_identity = Some(identity);
+        self
+    }
+
+    /// Trust an additional PEM-encoded root CA (or bundle of CAs)
+    pub fn with_root_certificate_pem(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.root_certificates.push(pem.into());
+        self
+    }
+
+    /// Set when failed calls are retried
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Log request and response bodies at debug level, masked by the policy
+    pub fn with_body_logging(mut self, policy: RedactionPolicy) -> Self {
+        self.body_logging = Some(policy);
+        self
+    }
+
+    /// Route calls according to a bank profile
+    ///
+    /// Only the paths and supported services are taken from the profile; use
+    /// [`AspspProfile::config`] to also apply its URL, credentials and certificates.
+    pub fn with_profile(mut self, profile: AspspProfile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    /// Path of an endpoint, failing if the bank does not offer its service
+    pub fn endpoint_path(&self, endpoint: Endpoint) -> Result<String> {
+        match &self.profile {
+            Some(profile) if !profile.supports(endpoint.service()) => Err(PolishApiError::Config(format!(
+                "ASPSP {} does not offer {}",
+                profile.id,
+                endpoint.service()
+            ))),
+            Some(profile) => Ok(profile.path(endpoint)),
+            None => Ok(endpoint.default_path()),
+        }
+    }
 }
 
 /// Main PolishAPI client
 pub struct PolishApiClient {
     config: Config,
     http_client: Client,
-    jws_signer: Option<JwsSigner>,
+    jws_signer: Option<Arc<dyn Signer>>,
+    response_verifier: Option<JwsVerifier>,
+    token_manager: Option<TokenManager>,
+    throttle: Option<Throttle>,
+    middlewares: Vec<Arc<dyn Middleware>>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Metrics>,
 }
 
 impl PolishApiClient {
     /// Create a new PolishAPI client
     pub async fn new(config: Config) -> Result<Self> {
-        let http_client = Client::builder()
+        let builder = Client::builder()
             .timeout(config.timeout)
-            .user_agent(&config.user_agent)
+            .user_agent(&config.user_agent);
+        let builder = tls::configure(builder, config.client_identity.as_ref(), &config.root_certificates)?;
+
+        let http_client = builder
             .build()
             .map_err(PolishApiError::Http)?;
 
@@ -75,20 +186,92 @@ impl PolishApiClient {
             config,
             http_client,
             jws_signer: None,
+            response_verifier: None,
+            token_manager: None,
+            throttle: None,
+            middlewares: middleware::built_in().into(),
+            #[cfg(feature = "metrics")]
+            metrics: None,
         })
     }
 
-    /// Set the JWS signer for request signing
-    pub fn with_jws_signer(mut self, signer: JwsSigner) -> Self {
+    /// Set the signing backend used for request JWS signatures
+    pub fn with_jws_signer(mut self, signer: Arc<dyn Signer>) -> Self {
         self.jws_signer = Some(signer);
         self
     }
 
+    /// Require every response body to carry a valid `X-JWS-SIGNATURE` from the bank
+    ///
+    /// The verifier should be built from the ASPSP's QSealC certificate.
+    pub fn with_response_verification(mut self, verifier: JwsVerifier) -> Self {
+        self.response_verifier = Some(verifier);
+        self
+    }
+
+    /// Manage the access token automatically
+    ///
+    /// AIS, PIS and CAF calls without an `Authorization` header get a fresh
+    /// bearer token for the session saved with [`TokenManager::store_response`].
+    pub fn with_token_manager(mut self, token_manager: TokenManager) -> Self {
+        self.token_manager = Some(token_manager);
+        self
+    }
+
+    /// Get the token manager, if configured
+    pub fn token_manager(&self) -> Option<&TokenManager> {
+        self.token_manager.as_ref()
+    }
+
+    /// Enforce client-side quotas on AIS, PIS and CAF calls
+    pub fn with_throttle(mut self, throttle: Throttle) -> Self {
+        self.throttle = Some(throttle);
+        self
+    }
+
+    /// Get the throttle, if configured
+    pub fn throttle(&self) -> Option<&Throttle> {
+        self.throttle.as_ref()
+    }
+
+    /// Record latency, errors, token refreshes and throttling rejections
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Get the metrics, if configured
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> Option<&Metrics> {
+        self.metrics.as_ref()
+    }
+
+    /// Add a middleware around every call
+    ///
+    /// Middlewares run in the order they are added, before the built-in
+    /// access token, throttling and signing middlewares.
+    pub fn with_middleware(mut self, middleware: Arc<dyn Middleware>) -> Self {
+        let position = self.middlewares.len() - middleware::BUILT_IN_COUNT;
+        self.middlewares.insert(position, middleware);
+        self
+    }
+
     /// Get the configuration
     pub fn config(&self) -> &Config {
         &self.config
     }
 
+    /// Use the client credentials issued by dynamic client registration for later calls
+    pub fn apply_registration(&mut self, registration: &RegisterResponse) {
+        self.config.apply_registration(registration);
+    }
+
+    /// Get the signing backend, if configured
+    pub fn jws_signer(&self) -> Option<&dyn Signer> {
+        self.jws_signer.as_deref()
+    }
+
     /// Get the HTTP client
     pub fn http_client(&self) -> &Client {
         &self.http_client
@@ -111,6 +294,148 @@ impl PolishApiClient {
         Ok(builder)
     }
 
+    /// Create a POST request builder for a PolishAPI endpoint
+    pub fn endpoint_builder(&self, endpoint: Endpoint) -> Result<RequestBuilder> {
+        let path = self.config.endpoint_path(endpoint)?;
+        self.request_builder(reqwest::Method::POST, &path)
+    }
+
+    /// Run a request body through the middleware chain and send it to an endpoint
+    ///
+    /// The body should already be prepared with [`PolishApiClient::prepare_request`]
+    /// and is checked with [`PolishApiRequest::validate`] first.
+    /// The call is traced in a `polishapi` span recording the service, endpoint,
+    /// request ID, bank, HTTP status, latency and number of retries.
+    pub async fn execute<R: PolishApiRequest + Serialize>(
+        &self,
+        endpoint: Endpoint,
+        request: &R,
+        headers: RequestHeaders,
+    ) -> Result<Response> {
+        let span = tracing::info_span!(
+            "polishapi",
+            service = %endpoint.service(),
+            endpoint = %endpoint,
+            request_id = %headers.x_request_id,
+            bank = %self.aspsp(),
+            status = tracing::field::Empty,
+            latency_ms = tracing::field::Empty,
+            retries = 0u32,
+        );
+
+        let started = Instant::now();
+        let result = match request.validate().and_then(|()| Ok(serde_json::to_value(request)?)) {
+            Ok(body) => {
+                let request = ApiRequest { endpoint, headers, extra_headers: HeaderMap::new(), body };
+                Next::new(self, &self.middlewares).run(request).instrument(span.clone()).await
+            }
+            Err(error) => Err(error),
+        };
+        let latency_ms = started.elapsed().as_millis() as u64;
+        span.record("latency_ms", latency_ms);
+
+        let _entered = span.enter();
+        match result {
+            Ok(mut response) => {
+                let status = response.status();
+                span.record("status", status.as_u16());
+                if status.is_success() {
+                    tracing::info!(status = status.as_u16(), latency_ms, "{} succeeded", endpoint);
+                } else {
+                    tracing::warn!(status = status.as_u16(), latency_ms, "{} failed", endpoint);
+                    #[cfg(feature = "metrics")]
+                    if let Some(metrics) = &self.metrics {
+                        metrics.record_error(self.aspsp(), endpoint, "api");
+                    }
+                }
+                // Lets parse_response attribute errors to the endpoint
+                response.extensions_mut().insert(endpoint);
+                Ok(response)
+            }
+            Err(error) => {
+                tracing::warn!(latency_ms, "{} failed: {}", endpoint, error);
+                self.record_error(endpoint, &error);
+                Err(error)
+            }
+        }
+    }
+
+    /// Record a failed call in the metrics
+    #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+    fn record_error(&self, endpoint: Endpoint, error: &PolishApiError) {
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            match error {
+                PolishApiError::QuotaExceeded { .. } => metrics.record_throttle_rejection(self.aspsp(), endpoint),
+                error => metrics.record_error(self.aspsp(), endpoint, error.kind()),
+            }
+        }
+    }
+
+    /// Build and send the request at the end of the middleware chain
+    pub(crate) async fn transport(&self, request: ApiRequest) -> Result<Response> {
+        let payload = serde_json::to_string(&request.body)?;
+        if let Some(policy) = &self.config.body_logging {
+            tracing::debug!(body = %policy.redact_json(&request.body), "request body");
+        }
+        let builder = self.endpoint_builder(request.endpoint)?;
+        let builder = self.add_auth_headers(builder, &request.headers).headers(request.extra_headers);
+
+        #[cfg(feature = "metrics")]
+        let started = Instant::now();
+        let result = self.send(request.endpoint, builder.body(payload)).await;
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.record_request(self.aspsp(), request.endpoint, started.elapsed(), &result);
+        }
+        result
+    }
+
+    /// Send a request to an endpoint, retrying according to the retry policy
+    ///
+    /// The request is re-sent unchanged, so its body and `X-REQUEST-ID` stay the
+    /// same. The last response is returned once retries are exhausted.
+    pub async fn send(&self, endpoint: Endpoint, builder: RequestBuilder) -> Result<Response> {
+        let policy = &self.config.retry_policy;
+        if !policy.allows(endpoint) {
+            return Ok(builder.send().await?);
+        }
+
+        let mut retry = 0;
+        loop {
+            // Streaming bodies cannot be cloned and are sent once
+            let attempt = match builder.try_clone() {
+                Some(attempt) => attempt,
+                None => return Ok(builder.send().await?),
+            };
+            retry += 1;
+            if retry > 1 {
+                tracing::Span::current().record("retries", retry - 1);
+            }
+
+            let delay = match attempt.send().await {
+                Ok(response) if retry::is_retryable_status(response.status()) => {
+                    match policy.delay(retry, retry::retry_after(response.headers())) {
+                        Some(delay) => {
+                            tracing::warn!("{} returned {}, retrying in {:?}", endpoint.operation(), response.status(), delay);
+                            delay
+                        }
+                        None => return Ok(response),
+                    }
+                }
+                Err(error) if retry::is_retryable_error(&error) => match policy.delay(retry, None) {
+                    Some(delay) => {
+                        tracing::warn!("{} failed: {}, retrying in {:?}", endpoint.operation(), error, delay);
+                        delay
+                    }
+                    None => return Err(error.into()),
+                },
+                result => return Ok(result?),
+            };
+            tokio::time::sleep(delay).await;
+        }
+    }
+
     /// Add authentication headers to a request
     pub fn add_auth_headers(&self, builder: RequestBuilder, headers: &RequestHeaders) -> RequestBuilder {
         builder
@@ -120,32 +445,381 @@ impl PolishApiClient {
             .header("X-REQUEST-ID", headers.x_request_id.to_string())
     }
 
+    /// Set the bearer `Authorization` header from the token manager
+    ///
+    /// Does nothing if the header is already set or no token manager is configured.
+    pub async fn apply_access_token(&self, headers: &mut RequestHeaders) -> Result<()> {
+        if !headers.authorization.is_empty() {
+            return Ok(());
+        }
+        if let Some(token_manager) = &self.token_manager {
+            if let Some(access_token) = token_manager.access_token(self).await? {
+                headers.authorization = format!("Bearer {}", access_token);
+            }
+        }
+        Ok(())
+    }
+
+    /// Count a call against the throttle's quotas
+    ///
+    /// Calls are keyed by the bank (profile ID, or host of the base URL), the
+    /// consent and the account. The consent is [`RequestHeaders::consent_id`]
+    /// or, if that is not set, the one of the token manager's token, so
+    /// refreshing the token keeps counting against the same quota. Calls with
+    /// an unknown consent share one counter per bank and account.
+    /// Does nothing if no throttle is configured.
+    pub async fn acquire_quota(
+        &self,
+        endpoint: Endpoint,
+        headers: &RequestHeaders,
+        account_number: Option<&str>,
+    ) -> Result<()> {
+        let throttle = match &self.throttle {
+            Some(throttle) => throttle,
+            None => return Ok(()),
+        };
+        let access_token = headers.authorization.strip_prefix("Bearer ").unwrap_or(&headers.authorization);
+        let consent_id = match (&headers.consent_id, &self.token_manager) {
+            (Some(consent_id), _) => Some(consent_id.clone()),
+            (None, Some(token_manager)) => token_manager.consent_id(access_token).await?,
+            (None, None) => None,
+        };
+        let call = ThrottledCall {
+            endpoint,
+            aspsp: self.aspsp(),
+            consent_id: consent_id.as_deref(),
+            account_number,
+            is_direct_psu: headers.psu.is_direct_psu.unwrap_or(false),
+        };
+        throttle.acquire(&call).await
+    }
+
+    /// Bank the client talks to: the profile ID, or host of the base URL
+    pub(crate) fn aspsp(&self) -> &str {
+        match &self.config.profile {
+            Some(profile) => profile.id.as_str(),
+            None => self.config.base_url.host_str().unwrap_or_default(),
+        }
+    }
+
+    /// Fill in the `requestHeader` of a request body before it is signed
+    ///
+    /// Values already set on the request are kept. The `requestId` and the
+    /// `X-REQUEST-ID` header, and the `isDirectPsu` flag and the PSU context,
+    /// are kept in sync.
+    pub fn prepare_request<R: PolishApiRequest>(&self, request: &mut R, headers: &mut RequestHeaders) {
+        let request_header = request.request_header_mut();
+        let token = headers.authorization.strip_prefix("Bearer ").unwrap_or(&headers.authorization);
+        if !token.is_empty() {
+            request_header.set_token(token);
+        }
+        request_header.set_callback(headers.callback_url.as_deref(), headers.api_key.as_deref());
+        if let Some(is_direct_psu) = request_header.direct_psu_mut() {
+            if is_direct_psu.is_none() {
+                *is_direct_psu = headers.psu.is_direct_psu;
+            } else {
+                headers.psu.is_direct_psu = *is_direct_psu;
+            }
+        }
+
+        let common = request_header.common_mut();
+        if common.request_id.is_nil() {
+            common.request_id = headers.x_request_id;
+        } else {
+            headers.x_request_id = common.request_id;
+        }
+        if common.send_date.is_none() {
+            common.send_date = Some(Utc::now());
+        }
+        fill(&mut common.tpp_id, self.config.tpp_id.as_deref());
+
+        let psu = &headers.psu;
+        fill(&mut common.user_agent, psu.user_agent.as_deref());
+        fill(&mut common.ip_address, psu.ip_address.as_deref());
+        if common.is_company_context.is_none() {
+            common.is_company_context = psu.is_company_context;
+        }
+        fill(&mut common.psu_identifier_type, psu.identifier_type.as_deref());
+        fill(&mut common.psu_identifier_value, psu.identifier_value.as_deref());
+        fill(&mut common.psu_context_identifier_type, psu.context_identifier_type.as_deref());
+        fill(&mut common.psu_context_identifier_value, psu.context_identifier_value.as_deref());
+    }
+
     /// Sign a request payload and return the JWS signature
     pub async fn sign_payload(&self, payload: &str) -> Result<String> {
         match &self.jws_signer {
-            Some(signer) => signer.sign(payload).await,
+            Some(signer) => sign_detached(signer.as_ref(), payload.as_bytes()).await,
             None => Err(PolishApiError::Config("JWS signer not configured".to_string())),
         }
     }
 
+    /// Read a successful response, verify its signature if enabled and deserialize the body
+    ///
+    /// Failures of responses returned by [`PolishApiClient::execute`] are recorded
+    /// in the metrics against their endpoint.
+    pub async fn parse_response<T: DeserializeOwned>(&self, response: Response) -> Result<T> {
+        let endpoint = response.extensions().get::<Endpoint>().copied();
+        let result = self.read_response(response).await;
+        if let (Some(endpoint), Err(error)) = (endpoint, &result) {
+            self.record_error(endpoint, error);
+        }
+        result
+    }
+
+    async fn read_response<T: DeserializeOwned>(&self, response: Response) -> Result<T> {
+        let signature = response
+            .headers()
+            .get("X-JWS-SIGNATURE")
+            .map(|value| value.to_str().map(str::to_owned));
+        let body = response.bytes().await?;
+        if let Some(policy) = &self.config.body_logging {
+            tracing::debug!(body = %policy.redact_body(&body), "response body");
+        }
+
+        if let Some(verifier) = &self.response_verifier {
+            let signature = match signature {
+                Some(Ok(signature)) => signature,
+                Some(Err(_)) => {
+                    return Err(PolishApiError::ResponseSignature(JwsError::Malformed(
+                        "X-JWS-SIGNATURE header is not valid ASCII".to_string(),
+                    )))
+                }
+                None => return Err(PolishApiError::ResponseSignature(JwsError::Missing)),
+            };
+
+            verifier.verify(&signature, &body).map_err(|e| match e {
+                PolishApiError::Jws(e) => PolishApiError::ResponseSignature(e),
+                other => other,
+            })?;
+        }
+
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    /// Read the body of an error response, logging it like [`PolishApiClient::parse_response`] does
+    pub async fn error_text(&self, response: Response) -> Result<String> {
+        let text = response.text().await?;
+        if let Some(policy) = &self.config.body_logging {
+            tracing::debug!(body = %policy.redact_body(text.as_bytes()), "error response body");
+        }
+        Ok(text)
+    }
+
     /// Get the authorization service
-    pub fn auth(&self) -> crate::auth::AuthService {
+    pub fn auth(&self) -> crate::auth::AuthService<'_> {
         crate::auth::AuthService::new(self)
     }
 
     /// Get the account information service
-    pub fn accounts(&self) -> crate::accounts::AccountService {
+    pub fn accounts(&self) -> crate::accounts::AccountService<'_> {
         crate::accounts::AccountService::new(self)
     }
 
     /// Get the payment initiation service
-    pub fn payments(&self) -> crate::payments::PaymentService {
+    pub fn payments(&self) -> crate::payments::PaymentService<'_> {
         crate::payments::PaymentService::new(self)
     }
 
     /// Get the funds confirmation service
-    pub fn funds(&self) -> crate::funds::FundsService {
+    pub fn funds(&self) -> crate::funds::FundsService<'_> {
         crate::funds::FundsService::new(self)
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, response_body, signer};
+    use crate::types::{GetAccountsRequest, GetAccountsResponse, PsuContext};
+    use crate::utils::HeadersBuilder;
+    use uuid::Uuid;
+
+    const RSA_CERTIFICATE: &str = include_str!("../testdata/rsa_certificate.pem");
+
+    async fn client(server: &mockito::Server, verify: bool) -> PolishApiClient {
+        let client = test_support::client(test_support::config(server)).await;
+        if verify {
+            client.with_response_verification(JwsVerifier::from_certificate_pem(RSA_CERTIFICATE).unwrap())
+        } else {
+            client
+        }
+    }
+
+    async fn get_accounts(client: &PolishApiClient) -> Result<GetAccountsResponse> {
+        let request = GetAccountsRequest {
+            request_header: Default::default(),
+            page_id: None,
+            per_page: None,
+        };
+        client.accounts().get_accounts(request, RequestHeaders::default()).await
+    }
+
+    fn accounts_body() -> String {
+        response_body(r#""accounts":[]"#)
+    }
+
+    #[tokio::test]
+    async fn test_signed_response_is_accepted() {
+        let mut server = mockito::Server::new_async().await;
+        let body = accounts_body();
+        let signature = sign_detached(&signer(), body.as_bytes()).await.unwrap();
+        let _mock = server
+            .mock("POST", "/v3_0.1/accounts/v3_0.1/getAccounts")
+            .with_header("X-JWS-SIGNATURE", &signature)
+            .with_body(&body)
+            .create_async()
+            .await;
+
+        let client = client(&server, true).await;
+        assert!(get_accounts(&client).await.unwrap().accounts.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_missing_signature_is_rejected() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("POST", "/v3_0.1/accounts/v3_0.1/getAccounts")
+            .with_body(accounts_body())
+            .create_async()
+            .await;
+
+        let verifying = client(&server, true).await;
+        assert!(matches!(get_accounts(&verifying).await, Err(PolishApiError::ResponseSignature(JwsError::Missing))));
+
+        // Verification is opt-in
+        let plain = client(&server, false).await;
+        assert!(get_accounts(&plain).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_tampered_body_is_rejected() {
+        let mut server = mockito::Server::new_async().await;
+        let signature = sign_detached(&signer(), accounts_body().as_bytes()).await.unwrap();
+        let _mock = server
+            .mock("POST", "/v3_0.1/accounts/v3_0.1/getAccounts")
+            .with_header("X-JWS-SIGNATURE", &signature)
+            .with_body(accounts_body())
+            .create_async()
+            .await;
+
+        let client = client(&server, true).await;
+        let error = get_accounts(&client).await.unwrap_err();
+        assert!(matches!(error, PolishApiError::ResponseSignature(JwsError::SignatureMismatch)));
+        assert!(std::error::Error::source(&error).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_request_header_is_populated() {
+        let mut server = mockito::Server::new_async().await;
+        let request_id = Uuid::new_v4();
+        let mock = server
+            .mock("POST", "/v3_0.1/accounts/v3_0.1/getAccounts")
+            .match_header("X-REQUEST-ID", request_id.to_string().as_str())
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "requestHeader": {
+                    "requestId": request_id,
+                    "tppId": "PSDPL-KNF-0000000000",
+                    "ipAddress": "192.0.2.1",
+                    "isCompanyContext": true,
+                    "token": "access-token"
+                }
+            })))
+            .with_body(accounts_body())
+            .create_async()
+            .await;
+
+        let config = test_support::config(&server).with_tpp_id("PSDPL-KNF-0000000000");
+        let client = test_support::client(config).await;
+        let headers = HeadersBuilder::new()
+            .authorization("access-token")
+            .request_id(request_id)
+            .psu_context(PsuContext {
+                ip_address: Some("192.0.2.1".to_string()),
+                is_company_context: Some(true),
+                ..Default::default()
+            })
+            .build();
+        let request = GetAccountsRequest { request_header: Default::default(), page_id: None, per_page: None };
+
+        client.accounts().get_accounts(request, headers).await.unwrap();
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_calls_follow_profile() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/openbanking/v2_1.1/accounts/v2_1.1/getAccounts")
+            .with_body(accounts_body())
+            .create_async()
+            .await;
+
+        let mut profile = AspspProfile::new("bank", &server.url()).unwrap();
+        profile.path_prefix = Some("/openbanking".to_string());
+        profile.api_version = "v2_1.1".to_string();
+        profile.services.remove(&crate::profile::Service::Payments);
+        let client = test_support::client(profile.config().unwrap()).await;
+
+        assert!(get_accounts(&client).await.is_ok());
+        mock.assert_async().await;
+
+        let request = crate::types::PaymentStatusRequest { request_header: Default::default(), payment_id: None, tpp_transaction_id: None };
+        let result = client.payments().get_payment_status(request, RequestHeaders::default()).await;
+        assert!(matches!(result, Err(PolishApiError::Config(_))));
+    }
+
+    /// Log output captured by the test subscriber
+    #[derive(Clone, Default)]
+    struct CapturedLog(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLog {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_calls_are_traced_with_redacted_bodies() {
+        let mut server = mockito::Server::new_async().await;
+        let body = response_body(
+            r#""accounts":[{"accountNumber":"PL61109010140000071219812874","accountType":{"code":"CACC"}}]"#,
+        );
+        let _mock = server
+            .mock("POST", "/v3_0.1/accounts/v3_0.1/getAccounts")
+            .with_body(body)
+            .create_async()
+            .await;
+
+        let log = CapturedLog::default();
+        let writer = log.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let config = test_support::config(&server).with_body_logging(RedactionPolicy::new());
+        let client = test_support::client(config).await;
+        let request_id = Uuid::new_v4();
+        let headers = HeadersBuilder::new().authorization("secret-access-token").request_id(request_id).build();
+        let request = GetAccountsRequest { request_header: Default::default(), page_id: None, per_page: None };
+        client.accounts().get_accounts(request, headers).await.unwrap();
+
+        let output = String::from_utf8(log.0.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("service=AIS"), "{}", output);
+        assert!(output.contains("endpoint=getAccounts"));
+        assert!(output.contains(&format!("request_id={}", request_id)));
+        assert!(output.contains("bank=127.0.0.1"));
+        assert!(output.contains("status=200"));
+        assert!(output.contains("request body"));
+        assert!(output.contains("****2874"));
+        assert!(!output.contains("secret-access-token"));
+        assert!(!output.contains("PL61109010140000071219812874"));
+    }
+}
@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::client::PolishApiClient;
+use crate::types::{
+    DeleteConsentRequest, PolishApiError, RequestHeaders, Result, ScopeDetails, TokenResponse,
+};
+
+/// Lifecycle state of a consent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConsentStatus {
+    Active,
+    /// `scopeTimeLimit` has passed
+    Expired,
+    /// Deleted through `deleteConsent`
+    Revoked,
+}
+
+/// Consent granted by a PSU
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsentRecord {
+    pub consent_id: String,
+    /// Application-defined identifier of the PSU
+    pub psu_id: String,
+    pub scope_details: ScopeDetails,
+    pub status: ConsentStatus,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ConsentRecord {
+    /// Time the consent expires
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        self.scope_details.scope_time_limit
+    }
+
+    /// Account numbers the consent grants privileges for
+    pub fn account_numbers(&self) -> impl Iterator<Item = &str> {
+        self.scope_details
+            .privilege_list
+            .iter()
+            .filter_map(|privileges| privileges.account_number.as_deref())
+    }
+
+    /// Whether the consent is active and has not reached its time limit
+    pub fn is_valid(&self) -> bool {
+        self.status == ConsentStatus::Active && self.expires_at() > Utc::now()
+    }
+}
+
+/// Storage backend for consent records
+///
+/// Implement this trait to keep consents in the application's database.
+#[async_trait]
+pub trait ConsentStore: Send + Sync {
+    /// Insert or replace a record
+    async fn save(&self, record: &ConsentRecord) -> Result<()>;
+
+    /// Get a record by consent ID
+    async fn get(&self, consent_id: &str) -> Result<Option<ConsentRecord>>;
+
+    /// List the records of a PSU
+    async fn list_for_psu(&self, psu_id: &str) -> Result<Vec<ConsentRecord>>;
+
+    /// List all records
+    async fn list(&self) -> Result<Vec<ConsentRecord>>;
+}
+
+/// Consent store that keeps records in memory
+#[derive(Debug, Default)]
+pub struct InMemoryConsentStore {
+    records: RwLock<HashMap<String, ConsentRecord>>,
+}
+
+impl InMemoryConsentStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ConsentStore for InMemoryConsentStore {
+    async fn save(&self, record: &ConsentRecord) -> Result<()> {
+        self.records.write().await.insert(record.consent_id.clone(), record.clone());
+        Ok(())
+    }
+
+    async fn get(&self, consent_id: &str) -> Result<Option<ConsentRecord>> {
+        Ok(self.records.read().await.get(consent_id).cloned())
+    }
+
+    async fn list_for_psu(&self, psu_id: &str) -> Result<Vec<ConsentRecord>> {
+        Ok(self.records.read().await.values().filter(|record| record.psu_id == psu_id).cloned().collect())
+    }
+
+    async fn list(&self) -> Result<Vec<ConsentRecord>> {
+        Ok(self.records.read().await.values().cloned().collect())
+    }
+}
+
+/// Tracks the consents granted by PSUs
+///
+/// Records consents from authorization results, marks them expired once
+/// `scopeTimeLimit` passes, warns about consents close to expiry and revokes
+/// them through `deleteConsent`.
+pub struct ConsentRegistry {
+    store: Arc<dyn ConsentStore>,
+    expiry_warning: Duration,
+}
+
+impl ConsentRegistry {
+    /// Create a registry backed by the given store, warning 7 days before expiry
+    pub fn new(store: Arc<dyn ConsentStore>) -> Self {
+        Self {
+            store,
+            expiry_warning: Duration::from_secs(7 * 24 * 60 * 60),
+        }
+    }
+
+    /// Set how long before expiry a consent is reported by [`ConsentRegistry::check_expiry`]
+    pub fn with_expiry_warning(mut self, warning: Duration) -> Self {
+        self.expiry_warning = warning;
+        self
+    }
+
+    /// Record a consent granted by a PSU
+    pub async fn record(&self, psu_id: impl Into<String>, scope_details: ScopeDetails) -> Result<ConsentRecord> {
+        let record = ConsentRecord {
+            consent_id: scope_details.consent_id.clone(),
+            psu_id: psu_id.into(),
+            scope_details,
+            status: ConsentStatus::Active,
+            created_at: Utc::now(),
+        };
+        self.store.save(&record).await?;
+        Ok(record)
+    }
+
+    /// Record the consent a token response was issued for
+    ///
+    /// Returns `None` if the response carries no `scope_details`.
+    pub async fn record_token_response(
+        &self,
+        psu_id: impl Into<String>,
+        response: &TokenResponse,
+    ) -> Result<Option<ConsentRecord>> {
+        match &response.scope_details {
+            Some(scope_details) => Ok(Some(self.record(psu_id, scope_details.clone()).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Get a consent, marking it expired if its time limit has passed
+    pub async fn get(&self, consent_id: &str) -> Result<Option<ConsentRecord>> {
+        match self.store.get(consent_id).await? {
+            Some(record) => Ok(Some(self.refresh_status(record).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Current status of a consent
+    pub async fn status(&self, consent_id: &str) -> Result<Option<ConsentStatus>> {
+        Ok(self.get(consent_id).await?.map(|record| record.status))
+    }
+
+    /// All consents of a PSU
+    pub async fn consents_for_psu(&self, psu_id: &str) -> Result<Vec<ConsentRecord>> {
+        let mut records = Vec::new();
+        for record in self.store.list_for_psu(psu_id).await? {
+            records.push(self.refresh_status(record).await?);
+        }
+        Ok(records)
+    }
+
+    /// Valid consents of a PSU that grant privileges for the given account
+    pub async fn valid_for_account(&self, psu_id: &str, account_number: &str) -> Result<Vec<ConsentRecord>> {
+        Ok(self
+            .consents_for_psu(psu_id)
+            .await?
+            .into_iter()
+            .filter(|record| record.is_valid() && record.account_numbers().any(|number| number == account_number))
+            .collect())
+    }
+
+    /// Mark expired consents and return the active ones close to expiry
+    ///
+    /// A warning is logged for every consent returned. Call this periodically
+    /// to ask PSUs to renew their consents in time.
+    pub async fn check_expiry(&self) -> Result<Vec<ConsentRecord>> {
+        let warning = chrono::Duration::from_std(self.expiry_warning).unwrap_or(chrono::Duration::MAX);
+        let deadline = Utc::now().checked_add_signed(warning).unwrap_or(DateTime::<Utc>::MAX_UTC);
+
+        let mut expiring = Vec::new();
+        for record in self.store.list().await? {
+            let record = self.refresh_status(record).await?;
+            if record.status == ConsentStatus::Active && record.expires_at() <= deadline {
+                tracing::warn!("Consent {} expires at {}", record.consent_id, record.expires_at());
+                expiring.push(record);
+            }
+        }
+        Ok(expiring)
+    }
+
+    /// Delete a consent at the ASPSP and mark it revoked
+    pub async fn revoke(&self, client: &PolishApiClient, consent_id: &str, headers: RequestHeaders) -> Result<()> {
+        let mut record = self
+            .store
+            .get(consent_id)
+            .await?
+            .ok_or_else(|| PolishApiError::Validation(format!("Unknown consent {}", consent_id)))?;
+
+        let request = DeleteConsentRequest {
+            request_header: Default::default(),
+            consent_id: consent_id.to_string(),
+        };
+        client.accounts().delete_consent(request, headers).await?;
+
+        record.status = ConsentStatus::Revoked;
+        self.store.save(&record).await
+    }
+
+    async fn refresh_status(&self, mut record: ConsentRecord) -> Result<ConsentRecord> {
+        if record.status == ConsentStatus::Active && record.expires_at() <= Utc::now() {
+            record.status = ConsentStatus::Expired;
+            self.store.save(&record).await?;
+        }
+        Ok(record)
+    }
+}
+
+impl std::fmt::Debug for ConsentRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConsentRegistry")
+            .field("expiry_warning", &self.expiry_warning)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use crate::types::{PrivilegeList, ScopeGroupType, ScopeUsageLimit, ThrottlingPolicy};
+
+    const ACCOUNT: &str = "PL61109010140000071219812874";
+
+    fn scope(consent_id: &str, expires_in: chrono::Duration) -> ScopeDetails {
+        ScopeDetails {
+            privilege_list: vec![PrivilegeList::for_account(ACCOUNT).with_get_account(ScopeUsageLimit::Multiple)],
+            scope_group_type: ScopeGroupType::Ais,
+            consent_id: consent_id.to_string(),
+            scope_time_limit: Utc::now() + expires_in,
+            throttling_policy: ThrottlingPolicy::Psd2Regulatory,
+        }
+    }
+
+    fn registry() -> ConsentRegistry {
+        ConsentRegistry::new(Arc::new(InMemoryConsentStore::new()))
+    }
+
+    #[tokio::test]
+    async fn test_valid_for_account() {
+        let registry = registry();
+        registry.record("psu-1", scope("valid", chrono::Duration::days(30))).await.unwrap();
+        registry.record("psu-1", scope("expired", chrono::Duration::days(-1))).await.unwrap();
+        registry.record("psu-2", scope("other-psu", chrono::Duration::days(30))).await.unwrap();
+
+        let valid = registry.valid_for_account("psu-1", ACCOUNT).await.unwrap();
+        assert_eq!(valid.iter().map(|record| record.consent_id.as_str()).collect::<Vec<_>>(), ["valid"]);
+        assert!(registry.valid_for_account("psu-1", "PL00").await.unwrap().is_empty());
+
+        assert_eq!(registry.status("expired").await.unwrap(), Some(ConsentStatus::Expired));
+        assert_eq!(registry.status("unknown").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_check_expiry() {
+        let registry = registry().with_expiry_warning(Duration::from_secs(3 * 24 * 60 * 60));
+        registry.record("psu-1", scope("soon", chrono::Duration::days(2))).await.unwrap();
+        registry.record("psu-1", scope("later", chrono::Duration::days(30))).await.unwrap();
+        registry.record("psu-1", scope("gone", chrono::Duration::seconds(-1))).await.unwrap();
+
+        let expiring = registry.check_expiry().await.unwrap();
+        assert_eq!(expiring.iter().map(|record| record.consent_id.as_str()).collect::<Vec<_>>(), ["soon"]);
+        assert_eq!(registry.status("gone").await.unwrap(), Some(ConsentStatus::Expired));
+    }
+
+    #[tokio::test]
+    async fn test_revoke() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v3_0.1/accounts/v3_0.1/deleteConsent")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({ "consentId": "valid" })))
+            .with_status(204)
+            .create_async()
+            .await;
+        let client = test_support::client(test_support::config(&server)).await;
+
+        let registry = registry();
+        registry.record("psu-1", scope("valid", chrono::Duration::days(30))).await.unwrap();
+        let headers = crate::utils::HeadersBuilder::new().authorization("token").build();
+        registry.revoke(&client, "valid", headers).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(registry.status("valid").await.unwrap(), Some(ConsentStatus::Revoked));
+        assert!(registry.valid_for_account("psu-1", ACCOUNT).await.unwrap().is_empty());
+
+        let unknown = registry.revoke(&client, "unknown", RequestHeaders::default()).await;
+        assert!(matches!(unknown, Err(PolishApiError::Validation(_))));
+    }
+}
@@ -1,13 +1,192 @@
-use ring::signature::{RsaKeyPair, RSA_PKCS1_SHA256};
+use async_trait::async_trait;
+use ring::signature::{
+    EcdsaKeyPair, KeyPair, RsaEncoding, RsaKeyPair, ECDSA_P256_SHA256_FIXED_SIGNING, RSA_PKCS1_SHA256,
+    RSA_PSS_SHA256,
+};
 use ring::rand::SystemRandom;
+use ring::rsa::PublicKeyComponents as RsaPublicKeyComponents;
+use ring::digest::{digest, SHA256};
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
-use serde_json::json;
-use crate::types::{PolishApiError, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64URL;
+use chrono::Utc;
+use serde_json::{json, Value};
+use crate::crypto::{JwsVerifier, Pkcs12Bundle, PrivateKey, Signer};
+use crate::types::{Jwk, JwsError, PolishApiError, Result};
 
-/// JWS signer for request signing
+/// JWS signature algorithms supported by the library
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JwsAlgorithm {
+    /// RSASSA-PKCS1-v1_5 using SHA-256
+    RS256,
+    /// RSASSA-PSS using SHA-256 and MGF1 with SHA-256
+    PS256,
+    /// ECDSA using P-256 and SHA-256
+    ES256,
+}
+
+impl JwsAlgorithm {
+    /// Name of the algorithm as used in the `alg` header
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JwsAlgorithm::RS256 => "RS256",
+            JwsAlgorithm::PS256 => "PS256",
+            JwsAlgorithm::ES256 => "ES256",
+        }
+    }
+
+    /// Parse an `alg` header value
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "RS256" => Some(JwsAlgorithm::RS256),
+            "PS256" => Some(JwsAlgorithm::PS256),
+            "ES256" => Some(JwsAlgorithm::ES256),
+            _ => None,
+        }
+    }
+}
+
+/// Certificate references added to the protected header
+///
+/// ASPSPs identify the TPP by the QSealC certificate referenced here.
+#[derive(Debug, Clone, Default)]
+pub struct CertificateHeaders {
+    chain: Vec<Vec<u8>>,
+    x5c: bool,
+    x5t_s256: bool,
+    x5u: Option<String>,
+}
+
+impl CertificateHeaders {
+    /// Use a DER certificate chain (leaf first); emits `x5c` and `x5t#S256` by default
+    pub fn from_der_chain(chain: Vec<Vec<u8>>) -> Result<Self> {
+        if chain.is_empty() {
+            return Err(PolishApiError::Crypto("Certificate chain is empty".to_string()));
+        }
+
+        Ok(Self { chain, x5c: true, x5t_s256: true, x5u: None })
+    }
+
+    /// Use a PEM certificate chain (leaf first); emits `x5c` and `x5t#S256` by default
+    pub fn from_pem_chain(pem: &str) -> Result<Self> {
+        let blocks = pem::parse_many(pem)
+            .map_err(|e| PolishApiError::Crypto(format!("Invalid certificate chain: {}", e)))?;
+        let chain = blocks
+            .into_iter()
+            .filter(|block| block.tag() == "CERTIFICATE")
+            .map(|block| block.into_contents())
+            .collect();
+        Self::from_der_chain(chain)
+    }
+
+    /// Reference the certificate by URL only (`x5u`)
+    pub fn from_url(url: impl Into<String>) -> Self {
+        Self { x5u: Some(url.into()), ..Self::default() }
+    }
+
+    /// Include or omit the full chain in `x5c`
+    pub fn with_x5c(mut self, enabled: bool) -> Self {
+        self.x5c = enabled;
+        self
+    }
+
+    /// Include or omit the SHA-256 thumbprint of the leaf certificate in `x5t#S256`
+    pub fn with_x5t_s256(mut self, enabled: bool) -> Self {
+        self.x5t_s256 = enabled;
+        self
+    }
+
+    /// Add an `x5u` URL pointing at the certificate chain
+    pub fn with_x5u(mut self, url: impl Into<String>) -> Self {
+        self.x5u = Some(url.into());
+        self
+    }
+
+    /// The chain as `x5c` values (standard base64 DER, leaf first)
+    pub fn x5c_chain(&self) -> Vec<String> {
+        self.chain.iter().map(|cert| BASE64.encode(cert)).collect()
+    }
+
+    /// Add the configured parameters to a protected header
+    fn apply(&self, header: &mut serde_json::Map<String, Value>) {
+        if self.x5c && !self.chain.is_empty() {
+            // x5c uses standard (not URL-safe) base64 of each DER certificate
+            let chain = self.x5c_chain().into_iter().map(Value::String).collect();
+            header.insert("x5c".to_string(), Value::Array(chain));
+        }
+        if let (true, Some(leaf)) = (self.x5t_s256, self.chain.first()) {
+            let thumbprint = BASE64URL.encode(digest(&SHA256, leaf).as_ref());
+            header.insert("x5t#S256".to_string(), Value::String(thumbprint));
+        }
+        if let Some(url) = &self.x5u {
+            header.insert("x5u".to_string(), Value::String(url.clone()));
+        }
+    }
+}
+
+/// Protected header fields shared by detached and compact signatures
+fn protected_header(signer: &dyn Signer) -> serde_json::Map<String, Value> {
+    let mut header = serde_json::Map::new();
+    header.insert("alg".to_string(), json!(signer.algorithm().as_str()));
+    header.insert("kid".to_string(), json!(signer.key_id()));
+    header.insert("iat".to_string(), json!(Utc::now().timestamp()));
+
+    if let Some(certificates) = signer.certificate_headers() {
+        certificates.apply(&mut header);
+    }
+    header
+}
+
+fn encode_header(header: &serde_json::Map<String, Value>) -> Result<String> {
+    let header_json = serde_json::to_string(header)
+        .map_err(|e| PolishApiError::Crypto(format!("Failed to serialize header: {}", e)))?;
+    Ok(BASE64URL.encode(header_json.as_bytes()))
+}
+
+/// Build a detached JWS (`header..signature`) for the payload using any signing backend
+pub async fn sign_detached(signer: &dyn Signer, payload: &[u8]) -> Result<String> {
+    let mut header = protected_header(signer);
+    header.insert("b64".to_string(), json!(false));
+    header.insert("crit".to_string(), json!(["b64"]));
+
+    let header_b64 = encode_header(&header)?;
+
+    // For detached JWS, we sign the concatenation of:
+    // base64url(header) + "." + payload
+    let mut signing_input = Vec::with_capacity(header_b64.len() + 1 + payload.len());
+    signing_input.extend_from_slice(header_b64.as_bytes());
+    signing_input.push(b'.');
+    signing_input.extend_from_slice(payload);
+
+    let signature = signer.sign(&signing_input).await?;
+    let signature_b64 = BASE64URL.encode(&signature);
+
+    // Return detached JWS in format: header..signature
+    Ok(format!("{}..{}", header_b64, signature_b64))
+}
+
+/// Build a compact JWS (`header.payload.signature`) with the given `typ`, e.g. a signed JWT
+pub async fn sign_compact(signer: &dyn Signer, typ: &str, payload: &[u8]) -> Result<String> {
+    let mut header = protected_header(signer);
+    header.insert("typ".to_string(), json!(typ));
+
+    let signing_input = format!("{}.{}", encode_header(&header)?, BASE64URL.encode(payload));
+    let signature = signer.sign(signing_input.as_bytes()).await?;
+
+    Ok(format!("{}.{}", signing_input, BASE64URL.encode(&signature)))
+}
+
+/// Private key held by [`JwsSigner`]
+enum SigningKey {
+    Rsa(RsaKeyPair),
+    EcP256(EcdsaKeyPair),
+}
+
+/// In-memory signing key (RSA or EC P-256)
 pub struct JwsSigner {
-    key_pair: RsaKeyPair,
+    key: SigningKey,
     key_id: String,
+    algorithm: JwsAlgorithm,
+    certificates: Option<CertificateHeaders>,
 }
 
 impl JwsSigner {
@@ -16,91 +195,330 @@ impl JwsSigner {
         let key_pair = RsaKeyPair::from_der(private_key_der)
             .map_err(|e| PolishApiError::Crypto(format!("Invalid private key: {:?}", e)))?;
 
-        Ok(Self { key_pair, key_id })
+        Ok(Self { key: SigningKey::Rsa(key_pair), key_id, algorithm: JwsAlgorithm::RS256, certificates: None })
     }
 
-    /// Create a new JWS signer from PEM-encoded private key
-    pub fn from_pem(private_key_pem: &str, key_id: String) -> Result<Self> {
-        // This is a simplified implementation - in practice, you'd want to use
-        // a proper PEM parser like the `pem` crate
-        let der_bytes = Self::pem_to_der(private_key_pem)?;
-        Self::new(&der_bytes, key_id)
+    /// Create a new JWS signer from a PKCS#8 DER private key
+    ///
+    /// RSA keys default to RS256 and EC P-256 keys to ES256.
+    pub fn from_pkcs8(private_key_der: &[u8], key_id: String) -> Result<Self> {
+        if let Ok(key_pair) = RsaKeyPair::from_pkcs8(private_key_der) {
+            return Ok(Self { key: SigningKey::Rsa(key_pair), key_id, algorithm: JwsAlgorithm::RS256, certificates: None });
+        }
+
+        let rng = SystemRandom::new();
+        let key_pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, private_key_der, &rng)
+            .map_err(|_| PolishApiError::Crypto("Invalid private key: expected RSA or EC P-256 PKCS#8".to_string()))?;
+
+        Ok(Self { key: SigningKey::EcP256(key_pair), key_id, algorithm: JwsAlgorithm::ES256, certificates: None })
     }
 
-    /// Sign a payload and return the detached JWS signature
-    pub async fn sign(&self, payload: &str) -> Result<String> {
-        let header = json!({
-            "alg": "RS256",
-            "kid": self.key_id,
-            "b64": false,
-            "crit": ["b64"]
-        });
+    /// Create a new JWS signer from a PEM private key (`RSA PRIVATE KEY` or `PRIVATE KEY`)
+    pub fn from_pem(private_key_pem: &str, key_id: String) -> Result<Self> {
+        Self::from_private_key(&PrivateKey::from_pem(private_key_pem)?, key_id)
+    }
 
-        let header_json = serde_json::to_string(&header)
-            .map_err(|e| PolishApiError::Crypto(format!("Failed to serialize header: {}", e)))?;
+    /// Create a new JWS signer from a passphrase-protected `ENCRYPTED PRIVATE KEY`
+    pub fn from_encrypted_pem(private_key_pem: &str, passphrase: &str, key_id: String) -> Result<Self> {
+        Self::from_private_key(&PrivateKey::from_encrypted_pem(private_key_pem, passphrase)?, key_id)
+    }
 
-        let header_b64 = BASE64.encode(header_json.as_bytes());
+    /// Create a new JWS signer from the private key of a PKCS#12 (`.p12` / `.pfx`) bundle
+    pub fn from_pkcs12_der(der: &[u8], password: &str, key_id: String) -> Result<Self> {
+        Self::from_private_key(&Pkcs12Bundle::from_der(der, password)?.private_key, key_id)
+    }
 
-        // For detached JWS, we sign the concatenation of:
-        // base64url(header) + "." + payload
-        let signing_input = format!("{}.{}", header_b64, payload);
+    /// Create a new JWS signer from a loaded private key
+    pub fn from_private_key(private_key: &PrivateKey, key_id: String) -> Result<Self> {
+        Self::from_pkcs8(private_key.pkcs8_der(), key_id)
+    }
 
-        let rng = SystemRandom::new();
-        let mut signature = vec![0u8; self.key_pair.public().modulus_len()];
+    /// Select the signing algorithm explicitly
+    ///
+    /// RSA keys support RS256 and PS256, EC P-256 keys support ES256.
+    pub fn with_algorithm(mut self, algorithm: JwsAlgorithm) -> Result<Self> {
+        let compatible = matches!(
+            (&self.key, algorithm),
+            (SigningKey::Rsa(_), JwsAlgorithm::RS256 | JwsAlgorithm::PS256)
+                | (SigningKey::EcP256(_), JwsAlgorithm::ES256)
+        );
+        if !compatible {
+            return Err(PolishApiError::Crypto(format!(
+                "Algorithm {} is not supported by this key type",
+                algorithm.as_str()
+            )));
+        }
 
-        self.key_pair
-            .sign(&RSA_PKCS1_SHA256, &rng, signing_input.as_bytes(), &mut signature)
-            .map_err(|e| PolishApiError::Crypto(format!("Signing failed: {:?}", e)))?;
+        self.algorithm = algorithm;
+        Ok(self)
+    }
 
-        let signature_b64 = BASE64.encode(&signature);
+    /// Reference the QSealC certificate chain in the protected header
+    pub fn with_certificate_headers(mut self, certificates: CertificateHeaders) -> Self {
+        self.certificates = Some(certificates);
+        self
+    }
 
-        // Return detached JWS in format: header..signature
-        Ok(format!("{}..{}", header_b64, signature_b64))
+    /// Public key as a JWK for publishing in the client's `jwks`
+    ///
+    /// The certificate chain is included as `x5c` when certificate headers are set.
+    pub fn public_jwk(&self) -> Jwk {
+        let mut jwk = Jwk {
+            kty: String::new(),
+            kid: Some(self.key_id.clone()),
+            key_use: Some("sig".to_string()),
+            alg: Some(self.algorithm.as_str().to_string()),
+            n: None,
+            e: None,
+            crv: None,
+            x: None,
+            y: None,
+            x5c: self.certificates.as_ref().map(CertificateHeaders::x5c_chain).filter(|chain| !chain.is_empty()),
+        };
+        match &self.key {
+            SigningKey::Rsa(key_pair) => {
+                let components = RsaPublicKeyComponents::<Vec<u8>>::from(key_pair.public());
+                jwk.kty = "RSA".to_string();
+                jwk.n = Some(BASE64URL.encode(&components.n));
+                jwk.e = Some(BASE64URL.encode(&components.e));
+            }
+            SigningKey::EcP256(key_pair) => {
+                // Uncompressed point: 0x04 || x || y
+                let point = key_pair.public_key().as_ref();
+                jwk.kty = "EC".to_string();
+                jwk.crv = Some("P-256".to_string());
+                jwk.x = Some(BASE64URL.encode(&point[1..33]));
+                jwk.y = Some(BASE64URL.encode(&point[33..65]));
+            }
+        }
+        jwk
     }
 
-    /// Verify a JWS signature (for testing purposes)
+    /// Verify a detached JWS against this signer's own public key
+    ///
+    /// Returns `Ok(false)` when the signature does not match the payload.
     pub fn verify(&self, jws: &str, payload: &str) -> Result<bool> {
-        let parts: Vec<&str> = jws.split('.').collect();
-        if parts.len() != 3 || !parts[1].is_empty() {
-            return Err(PolishApiError::Crypto("Invalid JWS format".to_string()));
+        let verifier = match &self.key {
+            SigningKey::Rsa(key_pair) => JwsVerifier::from_rsa_public_key_der(key_pair.public().as_ref()),
+            SigningKey::EcP256(key_pair) => JwsVerifier::from_ec_p256_public_key(key_pair.public_key().as_ref()),
+        };
+        match verifier.verify(jws, payload.as_bytes()) {
+            Ok(()) => Ok(true),
+            Err(PolishApiError::Jws(JwsError::SignatureMismatch)) => Ok(false),
+            Err(e) => Err(e),
         }
+    }
+}
 
-        let header_b64 = parts[0];
-        let signature_b64 = parts[2];
+#[async_trait]
+impl Signer for JwsSigner {
+    fn key_id(&self) -> &str {
+        &self.key_id
+    }
 
-        let _signing_input = format!("{}.{}", header_b64, payload);
-        let _signature = BASE64.decode(signature_b64)
-            .map_err(|e| PolishApiError::Crypto(format!("Invalid signature encoding: {}", e)))?;
+    fn algorithm(&self) -> JwsAlgorithm {
+        self.algorithm
+    }
 
-        // Note: ring doesn't provide RSA signature verification directly
-        // In a real implementation, you'd use a different crate like `rsa` for verification
-        // This is a placeholder implementation
-        Ok(true)
+    fn certificate_headers(&self) -> Option<&CertificateHeaders> {
+        self.certificates.as_ref()
     }
 
-    /// Convert PEM to DER (simplified implementation)
-    fn pem_to_der(pem: &str) -> Result<Vec<u8>> {
-        // This is a very basic PEM parser - in production, use the `pem` crate
-        let lines: Vec<&str> = pem.lines().collect();
-        let mut der_lines = Vec::new();
-        let mut in_key = false;
+    async fn sign(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let rng = SystemRandom::new();
 
-        for line in lines {
-            if line.starts_with("-----BEGIN") {
-                in_key = true;
-                continue;
-            }
-            if line.starts_with("-----END") {
-                break;
+        match &self.key {
+            SigningKey::Rsa(key_pair) => {
+                let padding: &'static dyn RsaEncoding = match self.algorithm {
+                    JwsAlgorithm::PS256 => &RSA_PSS_SHA256,
+                    _ => &RSA_PKCS1_SHA256,
+                };
+                let mut signature = vec![0u8; key_pair.public().modulus_len()];
+                key_pair
+                    .sign(padding, &rng, data, &mut signature)
+                    .map_err(|e| PolishApiError::Crypto(format!("Signing failed: {:?}", e)))?;
+                Ok(signature)
             }
-            if in_key {
-                der_lines.push(line);
+            SigningKey::EcP256(key_pair) => {
+                let signature = key_pair
+                    .sign(&rng, data)
+                    .map_err(|e| PolishApiError::Crypto(format!("Signing failed: {:?}", e)))?;
+                Ok(signature.as_ref().to_vec())
             }
         }
-
-        let der_b64 = der_lines.join("");
-        BASE64.decode(der_b64)
-            .map_err(|e| PolishApiError::Crypto(format!("Invalid PEM encoding: {}", e)))
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::KeyError;
+
+    const RSA_PRIVATE_KEY: &str = include_str!("../../testdata/rsa_private_key.pem");
+    const RSA_CERTIFICATE: &str = include_str!("../../testdata/rsa_certificate.pem");
+    const EC_PRIVATE_KEY: &str = include_str!("../../testdata/ec_private_key.pem");
+    const EC_CERTIFICATE: &str = include_str!("../../testdata/ec_certificate.pem");
+    const EC_PRIVATE_KEY_ENCRYPTED: &str = include_str!("../../testdata/ec_private_key_encrypted.pem");
+    const RSA_IDENTITY_P12: &[u8] = include_bytes!("../../testdata/rsa_identity.p12");
+    const PAYLOAD: &str = r#"{"requestHeader":{"requestId":"1"},"amount":"10.00"}"#;
+
+    async fn round_trip(signer: &JwsSigner, certificate_pem: &str) -> String {
+        let jws = sign_detached(signer, PAYLOAD.as_bytes()).await.unwrap();
+        let verifier = JwsVerifier::from_certificate_pem(certificate_pem).unwrap();
+        verifier.verify(&jws, PAYLOAD.as_bytes()).unwrap();
+        assert!(signer.verify(&jws, PAYLOAD).unwrap());
+        jws
+    }
+
+    fn header(jws: &str) -> Value {
+        let header = BASE64URL.decode(jws.split('.').next().unwrap()).unwrap();
+        serde_json::from_slice(&header).unwrap()
+    }
+
+    fn header_alg(jws: &str) -> String {
+        header(jws)["alg"].as_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_rs256_round_trip() {
+        let signer = JwsSigner::from_pem(RSA_PRIVATE_KEY, "rsa".to_string()).unwrap();
+        assert_eq!(signer.algorithm(), JwsAlgorithm::RS256);
+        assert_eq!(header_alg(&round_trip(&signer, RSA_CERTIFICATE).await), "RS256");
+    }
+
+    #[tokio::test]
+    async fn test_ps256_round_trip() {
+        let signer = JwsSigner::from_pem(RSA_PRIVATE_KEY, "rsa".to_string())
+            .unwrap()
+            .with_algorithm(JwsAlgorithm::PS256)
+            .unwrap();
+        assert_eq!(header_alg(&round_trip(&signer, RSA_CERTIFICATE).await), "PS256");
+    }
+
+    #[tokio::test]
+    async fn test_es256_selected_from_key_type() {
+        let signer = JwsSigner::from_pem(EC_PRIVATE_KEY, "ec".to_string()).unwrap();
+        assert_eq!(signer.algorithm(), JwsAlgorithm::ES256);
+        assert_eq!(header_alg(&round_trip(&signer, EC_CERTIFICATE).await), "ES256");
+    }
+
+    #[test]
+    fn test_incompatible_algorithm_is_rejected() {
+        let rsa = JwsSigner::from_pem(RSA_PRIVATE_KEY, "rsa".to_string()).unwrap();
+        assert!(rsa.with_algorithm(JwsAlgorithm::ES256).is_err());
+
+        let ec = JwsSigner::from_pem(EC_PRIVATE_KEY, "ec".to_string()).unwrap();
+        assert!(ec.with_algorithm(JwsAlgorithm::PS256).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_certificate_headers() {
+        let certificates = CertificateHeaders::from_pem_chain(RSA_CERTIFICATE)
+            .unwrap()
+            .with_x5u("https://tpp.example.com/qseal.pem");
+        let signer = JwsSigner::from_pem(RSA_PRIVATE_KEY, "rsa".to_string())
+            .unwrap()
+            .with_certificate_headers(certificates);
+
+        let jws = round_trip(&signer, RSA_CERTIFICATE).await;
+        let header = header(&jws);
+
+        let leaf = pem::parse(RSA_CERTIFICATE).unwrap().into_contents();
+        assert_eq!(header["x5c"], json!([BASE64.encode(&leaf)]));
+        assert_eq!(header["x5t#S256"], BASE64URL.encode(digest(&SHA256, &leaf).as_ref()));
+        assert_eq!(header["x5u"], "https://tpp.example.com/qseal.pem");
+        assert!(header["iat"].as_i64().unwrap() > 0);
+    }
+
+    #[tokio::test]
+    async fn test_certificate_headers_are_configurable() {
+        let certificates = CertificateHeaders::from_pem_chain(RSA_CERTIFICATE)
+            .unwrap()
+            .with_x5c(false);
+        let signer = JwsSigner::from_pem(RSA_PRIVATE_KEY, "rsa".to_string())
+            .unwrap()
+            .with_certificate_headers(certificates);
+
+        let header = header(&round_trip(&signer, RSA_CERTIFICATE).await);
+        assert!(header.get("x5c").is_none());
+        assert!(header.get("x5t#S256").is_some());
+        assert!(header.get("x5u").is_none());
+
+        assert!(CertificateHeaders::from_pem_chain("").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_and_pkcs12_keys() {
+        let signer = JwsSigner::from_encrypted_pem(EC_PRIVATE_KEY_ENCRYPTED, "secret", "ec".to_string()).unwrap();
+        assert_eq!(header_alg(&round_trip(&signer, EC_CERTIFICATE).await), "ES256");
+
+        let signer = JwsSigner::from_pkcs12_der(RSA_IDENTITY_P12, "secret", "rsa".to_string()).unwrap();
+        assert_eq!(header_alg(&round_trip(&signer, RSA_CERTIFICATE).await), "RS256");
+
+        assert!(matches!(
+            JwsSigner::from_pem(EC_PRIVATE_KEY_ENCRYPTED, "ec".to_string()),
+            Err(PolishApiError::Key(KeyError::PassphraseRequired))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_output_is_unpadded_base64url() {
+        let signer = JwsSigner::from_pem(RSA_PRIVATE_KEY, "rsa".to_string()).unwrap();
+        for _ in 0..8 {
+            let jws = sign_detached(&signer, PAYLOAD.as_bytes()).await.unwrap();
+            assert!(!jws.contains(['+', '/', '=']), "{}", jws);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_jsonwebtoken_verifies_our_signatures() {
+        let public_key = include_str!("../../testdata/rsa_public_key.pem");
+        let decoding_key = jsonwebtoken::DecodingKey::from_rsa_pem(public_key.as_bytes()).unwrap();
+
+        for (algorithm, jwt_algorithm) in [
+            (JwsAlgorithm::RS256, jsonwebtoken::Algorithm::RS256),
+            (JwsAlgorithm::PS256, jsonwebtoken::Algorithm::PS256),
+        ] {
+            let signer = JwsSigner::from_pem(RSA_PRIVATE_KEY, "rsa".to_string())
+                .unwrap()
+                .with_algorithm(algorithm)
+                .unwrap();
+            let jws = sign_detached(&signer, PAYLOAD.as_bytes()).await.unwrap();
+
+            let header = jsonwebtoken::decode_header(&jws).unwrap();
+            assert_eq!(header.alg, jwt_algorithm);
+            assert_eq!(header.kid.as_deref(), Some("rsa"));
+
+            let (header_b64, signature_b64) = jws.split_once("..").unwrap();
+            let signing_input = format!("{}.{}", header_b64, PAYLOAD);
+            assert!(jsonwebtoken::crypto::verify(
+                signature_b64,
+                signing_input.as_bytes(),
+                &decoding_key,
+                jwt_algorithm
+            )
+            .unwrap());
+        }
+    }
+
+    #[test]
+    fn test_we_verify_jsonwebtoken_signatures() {
+        let cases = [
+            (jsonwebtoken::Algorithm::RS256, jsonwebtoken::EncodingKey::from_rsa_pem(RSA_PRIVATE_KEY.as_bytes()).unwrap(), RSA_CERTIFICATE),
+            (jsonwebtoken::Algorithm::PS256, jsonwebtoken::EncodingKey::from_rsa_pem(RSA_PRIVATE_KEY.as_bytes()).unwrap(), RSA_CERTIFICATE),
+            (jsonwebtoken::Algorithm::ES256, jsonwebtoken::EncodingKey::from_ec_pem(EC_PRIVATE_KEY.as_bytes()).unwrap(), EC_CERTIFICATE),
+        ];
+
+        for (algorithm, key, certificate) in cases {
+            let header = json!({"alg": format!("{:?}", algorithm), "b64": false, "crit": ["b64"]});
+            let header_b64 = BASE64URL.encode(header.to_string());
+            let signing_input = format!("{}.{}", header_b64, PAYLOAD);
+            let signature = jsonwebtoken::crypto::sign(signing_input.as_bytes(), &key, algorithm).unwrap();
+
+            let verifier = JwsVerifier::from_certificate_pem(certificate).unwrap();
+            verifier
+                .verify(&format!("{}..{}", header_b64, signature), PAYLOAD.as_bytes())
+                .unwrap();
+        }
+    }
+}
@@ -1,2 +1,9 @@
 pub mod jws;
-pub use jws::JwsSigner;
+pub mod keys;
+pub mod signer;
+pub mod verifier;
+
+pub use jws::{sign_compact, sign_detached, CertificateHeaders, JwsAlgorithm, JwsSigner};
+pub use keys::{KeyFormat, Pkcs12Bundle, PrivateKey};
+pub use signer::{CommandSigner, Signer};
+pub use verifier::JwsVerifier;
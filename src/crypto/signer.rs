@@ -0,0 +1,166 @@
+use std::process::Stdio;
+
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::crypto::{CertificateHeaders, JwsAlgorithm};
+use crate::types::{PolishApiError, Result};
+
+/// Signing backend used to produce JWS signatures
+///
+/// Implement this trait to keep the QSealC key outside the process,
+/// e.g. in an HSM (PKCS#11), a remote KMS or a signing sidecar.
+#[async_trait]
+pub trait Signer: Send + Sync {
+    /// Key identifier placed in the `kid` header
+    fn key_id(&self) -> &str;
+
+    /// Algorithm placed in the `alg` header and used by [`Signer::sign`]
+    fn algorithm(&self) -> JwsAlgorithm;
+
+    /// Certificate references (`x5c`, `x5t#S256`, `x5u`) for the protected header
+    fn certificate_headers(&self) -> Option<&CertificateHeaders> {
+        None
+    }
+
+    /// Sign the JWS signing input and return the raw signature bytes
+    ///
+    /// For ES256 the signature must be the fixed-size `r || s` form.
+    async fn sign(&self, data: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// Signer that delegates to an external command
+///
+/// The signing input is written to the command's stdin and the raw
+/// signature is read from its stdout, e.g.
+/// `openssl dgst -sha256 -sign key.pem` for RS256.
+#[derive(Debug, Clone)]
+pub struct CommandSigner {
+    program: String,
+    args: Vec<String>,
+    key_id: String,
+    algorithm: JwsAlgorithm,
+    certificates: Option<CertificateHeaders>,
+}
+
+impl CommandSigner {
+    /// Create a new command signer
+    pub fn new(program: impl Into<String>, key_id: impl Into<String>, algorithm: JwsAlgorithm) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            key_id: key_id.into(),
+            algorithm,
+            certificates: None,
+        }
+    }
+
+    /// Add an argument passed to the command
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Add several arguments passed to the command
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Reference the QSealC certificate chain in the protected header
+    pub fn with_certificate_headers(mut self, certificates: CertificateHeaders) -> Self {
+        self.certificates = Some(certificates);
+        self
+    }
+}
+
+#[async_trait]
+impl Signer for CommandSigner {
+    fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    fn algorithm(&self) -> JwsAlgorithm {
+        self.algorithm
+    }
+
+    fn certificate_headers(&self) -> Option<&CertificateHeaders> {
+        self.certificates.as_ref()
+    }
+
+    async fn sign(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| PolishApiError::Crypto(format!("Failed to start signing command: {}", e)))?;
+
+        let mut stdin = child.stdin.take()
+            .ok_or_else(|| PolishApiError::Crypto("Signing command stdin unavailable".to_string()))?;
+        // A command that exits without reading its input is reported through
+        // its exit status and stderr below
+        match stdin.write_all(data).await {
+            Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => {
+                return Err(PolishApiError::Crypto(format!("Failed to write signing input: {}", e)))
+            }
+            _ => {}
+        }
+        drop(stdin);
+
+        let output = child.wait_with_output().await
+            .map_err(|e| PolishApiError::Crypto(format!("Signing command failed: {}", e)))?;
+
+        if !output.status.success() {
+            return Err(PolishApiError::Crypto(format!(
+                "Signing command exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        if output.stdout.is_empty() {
+            return Err(PolishApiError::Crypto("Signing command produced no signature".to_string()));
+        }
+
+        Ok(output.stdout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::sign_detached;
+
+    #[tokio::test]
+    async fn test_command_signer_round_trip() {
+        // Stub standing in for a signing sidecar: echoes a marker and the signing input
+        let signer = CommandSigner::new("sh", "sidecar-key", JwsAlgorithm::RS256)
+            .args(["-c", "printf 'sig:'; cat"]);
+
+        assert_eq!(signer.sign(b"data").await.unwrap(), b"sig:data");
+
+        let jws = sign_detached(&signer, br#"{"requestHeader":{"requestId":"1"}}"#).await.unwrap();
+        let parts: Vec<&str> = jws.split('.').collect();
+        assert_eq!(parts.len(), 3);
+        assert!(parts[1].is_empty());
+        assert!(!parts[2].is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_command_signer_failure() {
+        let signer = CommandSigner::new("sh", "sidecar-key", JwsAlgorithm::RS256)
+            .args(["-c", "echo 'key locked' >&2; exit 3"]);
+
+        match signer.sign(b"data").await {
+            Err(PolishApiError::Crypto(message)) => assert!(message.contains("key locked")),
+            other => panic!("expected crypto error, got {:?}", other),
+        }
+    }
+}
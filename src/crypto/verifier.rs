@@ -0,0 +1,319 @@
+use ring::signature::{
+    UnparsedPublicKey, VerificationAlgorithm, ECDSA_P256_SHA256_FIXED,
+    RSA_PKCS1_2048_8192_SHA256, RSA_PSS_2048_8192_SHA256,
+};
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD as BASE64URL};
+use serde_json::Value;
+use x509_parser::oid_registry::{OID_EC_P256, OID_KEY_TYPE_EC_PUBLIC_KEY, OID_PKCS1_RSAENCRYPTION};
+use x509_parser::prelude::{FromDer, SubjectPublicKeyInfo};
+
+use crate::crypto::JwsAlgorithm;
+use crate::types::{JwsError, Result};
+
+/// Public key material used for verification
+#[derive(Debug, Clone)]
+enum PublicKey {
+    /// PKCS#1 `RSAPublicKey` DER
+    Rsa(Vec<u8>),
+    /// Uncompressed P-256 point
+    EcP256(Vec<u8>),
+}
+
+/// Verifier for detached (`b64: false`) JWS signatures
+#[derive(Debug, Clone)]
+pub struct JwsVerifier {
+    key: PublicKey,
+    legacy_padding: bool,
+}
+
+impl JwsVerifier {
+    /// Create a verifier from a PKCS#1 `RSAPublicKey` DER
+    pub fn from_rsa_public_key_der(der: &[u8]) -> Self {
+        Self { key: PublicKey::Rsa(der.to_vec()), legacy_padding: false }
+    }
+
+    /// Create a verifier from an uncompressed EC P-256 public key point
+    pub fn from_ec_p256_public_key(point: &[u8]) -> Self {
+        Self { key: PublicKey::EcP256(point.to_vec()), legacy_padding: false }
+    }
+
+    /// Create a verifier from a DER-encoded `SubjectPublicKeyInfo` (RSA or EC P-256)
+    pub fn from_public_key_der(der: &[u8]) -> Result<Self> {
+        let (_, spki) = SubjectPublicKeyInfo::from_der(der)
+            .map_err(|e| JwsError::InvalidKey(format!("invalid SubjectPublicKeyInfo: {}", e)))?;
+        Self::from_spki(&spki)
+    }
+
+    /// Create a verifier from a PEM public key (`PUBLIC KEY` or `RSA PUBLIC KEY`)
+    pub fn from_public_key_pem(pem: &str) -> Result<Self> {
+        let block = Self::parse_pem(pem)?;
+        match block.tag() {
+            "PUBLIC KEY" => Self::from_public_key_der(block.contents()),
+            "RSA PUBLIC KEY" => Ok(Self::from_rsa_public_key_der(block.contents())),
+            tag => Err(JwsError::InvalidKey(format!("unexpected PEM label: {}", tag)).into()),
+        }
+    }
+
+    /// Create a verifier from a DER-encoded X.509 certificate
+    pub fn from_certificate_der(der: &[u8]) -> Result<Self> {
+        let (_, certificate) = x509_parser::parse_x509_certificate(der)
+            .map_err(|e| JwsError::InvalidKey(format!("invalid certificate: {}", e)))?;
+        Self::from_spki(certificate.public_key())
+    }
+
+    /// Create a verifier from a PEM-encoded X.509 certificate
+    pub fn from_certificate_pem(pem: &str) -> Result<Self> {
+        let block = Self::parse_pem(pem)?;
+        if block.tag() != "CERTIFICATE" {
+            return Err(JwsError::InvalidKey(format!("unexpected PEM label: {}", block.tag())).into());
+        }
+        Self::from_certificate_der(block.contents())
+    }
+
+    /// Also accept padded or standard-alphabet base64 produced by legacy implementations
+    ///
+    /// RFC 7515 requires unpadded base64url; leave this off unless a peer is known to need it.
+    pub fn with_legacy_padding(mut self, enabled: bool) -> Self {
+        self.legacy_padding = enabled;
+        self
+    }
+
+    /// Verify a detached JWS (`header..signature`) over the raw payload bytes
+    pub fn verify(&self, jws: &str, payload: &[u8]) -> Result<()> {
+        let mut parts = jws.split('.');
+        let (header_b64, body, signature_b64) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(header), Some(body), Some(signature), None) => (header, body, signature),
+            _ => return Err(JwsError::Malformed("expected three dot-separated parts".to_string()).into()),
+        };
+        if !body.is_empty() {
+            return Err(JwsError::Malformed("payload must be detached".to_string()).into());
+        }
+
+        let algorithm = self.check_header(header_b64)?;
+
+        let signature = self.decode(signature_b64)
+            .map_err(|e| JwsError::Malformed(format!("invalid signature encoding: {}", e)))?;
+
+        // With b64=false the payload is used verbatim in the signing input
+        let mut signing_input = Vec::with_capacity(header_b64.len() + 1 + payload.len());
+        signing_input.extend_from_slice(header_b64.as_bytes());
+        signing_input.push(b'.');
+        signing_input.extend_from_slice(payload);
+
+        let (verification, key): (&'static dyn VerificationAlgorithm, &[u8]) = match (algorithm, &self.key) {
+            (JwsAlgorithm::RS256, PublicKey::Rsa(key)) => (&RSA_PKCS1_2048_8192_SHA256, key),
+            (JwsAlgorithm::PS256, PublicKey::Rsa(key)) => (&RSA_PSS_2048_8192_SHA256, key),
+            (JwsAlgorithm::ES256, PublicKey::EcP256(key)) => (&ECDSA_P256_SHA256_FIXED, key),
+            (algorithm, _) => return Err(JwsError::KeyMismatch(algorithm.as_str().to_string()).into()),
+        };
+
+        UnparsedPublicKey::new(verification, key)
+            .verify(&signing_input, &signature)
+            .map_err(|_| JwsError::SignatureMismatch.into())
+    }
+
+    /// Decode the protected header and validate `alg`, `b64` and `crit`
+    fn check_header(&self, header_b64: &str) -> Result<JwsAlgorithm> {
+        let header_json = self.decode(header_b64)
+            .map_err(|e| JwsError::InvalidHeader(format!("invalid encoding: {}", e)))?;
+        let header: Value = serde_json::from_slice(&header_json)
+            .map_err(|e| JwsError::InvalidHeader(format!("invalid JSON: {}", e)))?;
+        let header = header.as_object()
+            .ok_or_else(|| JwsError::InvalidHeader("header is not a JSON object".to_string()))?;
+
+        let alg = header.get("alg")
+            .and_then(Value::as_str)
+            .ok_or_else(|| JwsError::InvalidHeader("missing \"alg\"".to_string()))?;
+        let algorithm = JwsAlgorithm::from_name(alg)
+            .ok_or_else(|| JwsError::UnsupportedAlgorithm(alg.to_string()))?;
+
+        if header.get("b64") != Some(&Value::Bool(false)) {
+            return Err(JwsError::InvalidHeader("detached signatures require \"b64\": false".to_string()).into());
+        }
+
+        let crit = match header.get("crit") {
+            Some(Value::Array(crit)) => crit,
+            Some(_) => return Err(JwsError::InvalidHeader("\"crit\" must be an array".to_string()).into()),
+            None => return Err(JwsError::MissingCrit.into()),
+        };
+        let mut has_b64 = false;
+        for entry in crit {
+            match entry.as_str() {
+                Some("b64") => has_b64 = true,
+                Some(other) => return Err(JwsError::UnsupportedCritical(other.to_string()).into()),
+                None => return Err(JwsError::InvalidHeader("\"crit\" entries must be strings".to_string()).into()),
+            }
+        }
+        if !has_b64 {
+            return Err(JwsError::MissingCrit.into());
+        }
+
+        Ok(algorithm)
+    }
+
+    /// Decode base64url, normalising legacy encodings when enabled
+    fn decode(&self, input: &str) -> std::result::Result<Vec<u8>, base64::DecodeError> {
+        if !self.legacy_padding {
+            return BASE64URL.decode(input);
+        }
+
+        let normalised: String = input
+            .trim_end_matches('=')
+            .chars()
+            .map(|c| match c {
+                '+' => '-',
+                '/' => '_',
+                c => c,
+            })
+            .collect();
+        BASE64URL.decode(normalised)
+    }
+
+    fn from_spki(spki: &SubjectPublicKeyInfo<'_>) -> Result<Self> {
+        let key = spki.subject_public_key.data.to_vec();
+        if spki.algorithm.algorithm == OID_PKCS1_RSAENCRYPTION {
+            return Ok(Self { key: PublicKey::Rsa(key), legacy_padding: false });
+        }
+
+        if spki.algorithm.algorithm == OID_KEY_TYPE_EC_PUBLIC_KEY {
+            let curve = spki.algorithm.parameters.as_ref().and_then(|p| p.as_oid().ok());
+            return match curve {
+                Some(curve) if curve == OID_EC_P256 => Ok(Self { key: PublicKey::EcP256(key), legacy_padding: false }),
+                _ => Err(JwsError::InvalidKey("only the P-256 curve is supported".to_string()).into()),
+            };
+        }
+
+        Err(JwsError::InvalidKey(format!("unsupported key algorithm {}", spki.algorithm.algorithm)).into())
+    }
+
+    fn parse_pem(pem: &str) -> Result<pem::Pem> {
+        pem::parse(pem)
+            .map_err(|e| JwsError::InvalidKey(format!("invalid PEM: {}", e)).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{sign_detached, JwsSigner};
+    use crate::types::PolishApiError;
+    use ring::rand::SystemRandom;
+    use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+
+    const RSA_PRIVATE_KEY: &str = include_str!("../../testdata/rsa_private_key.pem");
+    const RSA_PUBLIC_KEY: &str = include_str!("../../testdata/rsa_public_key.pem");
+    const RSA_CERTIFICATE: &str = include_str!("../../testdata/rsa_certificate.pem");
+    const PAYLOAD: &str = r#"{"requestHeader":{"requestId":"d0f0e1a2"}}"#;
+
+    fn signer() -> JwsSigner {
+        JwsSigner::from_pem(RSA_PRIVATE_KEY, "test-key".to_string()).unwrap()
+    }
+
+    fn encode_header(header: Value) -> String {
+        BASE64URL.encode(header.to_string())
+    }
+
+    fn jws_error(result: Result<()>) -> JwsError {
+        match result {
+            Err(PolishApiError::Jws(e)) => e,
+            other => panic!("expected JWS error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verify_with_public_key_and_certificate() {
+        let jws = sign_detached(&signer(), PAYLOAD.as_bytes()).await.unwrap();
+
+        let from_key = JwsVerifier::from_public_key_pem(RSA_PUBLIC_KEY).unwrap();
+        assert!(from_key.verify(&jws, PAYLOAD.as_bytes()).is_ok());
+
+        let from_cert = JwsVerifier::from_certificate_pem(RSA_CERTIFICATE).unwrap();
+        assert!(from_cert.verify(&jws, PAYLOAD.as_bytes()).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_signature_mismatch() {
+        let jws = sign_detached(&signer(), PAYLOAD.as_bytes()).await.unwrap();
+        let verifier = JwsVerifier::from_certificate_pem(RSA_CERTIFICATE).unwrap();
+
+        let tampered = PAYLOAD.replace("d0f0", "ffff");
+        assert_eq!(jws_error(verifier.verify(&jws, tampered.as_bytes())), JwsError::SignatureMismatch);
+        assert!(!signer().verify(&jws, &tampered).unwrap());
+        assert!(signer().verify(&jws, PAYLOAD).unwrap());
+    }
+
+    #[test]
+    fn test_malformed_and_bad_headers() {
+        let verifier = JwsVerifier::from_public_key_pem(RSA_PUBLIC_KEY).unwrap();
+
+        assert!(matches!(jws_error(verifier.verify("abc", b"")), JwsError::Malformed(_)));
+        assert!(matches!(jws_error(verifier.verify("a.b.c", b"")), JwsError::Malformed(_)));
+        assert!(matches!(jws_error(verifier.verify("!!!..AAAA", b"")), JwsError::InvalidHeader(_)));
+
+        let no_alg = encode_header(serde_json::json!({"b64": false, "crit": ["b64"]}));
+        assert!(matches!(jws_error(verifier.verify(&format!("{}..AAAA", no_alg), b"")), JwsError::InvalidHeader(_)));
+    }
+
+    #[test]
+    fn test_unsupported_algorithm_and_crit() {
+        let verifier = JwsVerifier::from_public_key_pem(RSA_PUBLIC_KEY).unwrap();
+
+        let hs256 = encode_header(serde_json::json!({"alg": "HS256", "b64": false, "crit": ["b64"]}));
+        assert_eq!(
+            jws_error(verifier.verify(&format!("{}..AAAA", hs256), b"")),
+            JwsError::UnsupportedAlgorithm("HS256".to_string())
+        );
+
+        let no_crit = encode_header(serde_json::json!({"alg": "RS256", "b64": false}));
+        assert_eq!(jws_error(verifier.verify(&format!("{}..AAAA", no_crit), b"")), JwsError::MissingCrit);
+
+        let unknown_crit = encode_header(serde_json::json!({"alg": "RS256", "b64": false, "crit": ["b64", "exp"]}));
+        assert_eq!(
+            jws_error(verifier.verify(&format!("{}..AAAA", unknown_crit), b"")),
+            JwsError::UnsupportedCritical("exp".to_string())
+        );
+    }
+
+    #[test]
+    fn test_es256_signature() {
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng).unwrap();
+        let key_pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8.as_ref(), &rng).unwrap();
+
+        let header = encode_header(serde_json::json!({"alg": "ES256", "b64": false, "crit": ["b64"]}));
+        let signature = key_pair.sign(&rng, format!("{}.{}", header, PAYLOAD).as_bytes()).unwrap();
+        let jws = format!("{}..{}", header, BASE64URL.encode(signature.as_ref()));
+
+        let verifier = JwsVerifier::from_ec_p256_public_key(key_pair.public_key().as_ref());
+        assert!(verifier.verify(&jws, PAYLOAD.as_bytes()).is_ok());
+
+        // An RSA key must not be accepted for an ES256 signature
+        let rsa = JwsVerifier::from_public_key_pem(RSA_PUBLIC_KEY).unwrap();
+        assert_eq!(jws_error(rsa.verify(&jws, PAYLOAD.as_bytes())), JwsError::KeyMismatch("ES256".to_string()));
+    }
+
+    #[test]
+    fn test_legacy_padding_requires_opt_in() {
+        use base64::engine::general_purpose::STANDARD;
+
+        // Padded standard base64, as produced by older PolishAPI implementations
+        let header = STANDARD.encode(serde_json::json!({"alg": "RS256", "b64": false, "crit": ["b64"]}).to_string());
+        let key = ring::signature::RsaKeyPair::from_der(&pem::parse(RSA_PRIVATE_KEY).unwrap().into_contents()).unwrap();
+        let mut signature = vec![0u8; key.public().modulus_len()];
+        key.sign(
+            &ring::signature::RSA_PKCS1_SHA256,
+            &SystemRandom::new(),
+            format!("{}.{}", header, PAYLOAD).as_bytes(),
+            &mut signature,
+        )
+        .unwrap();
+        let legacy = format!("{}..{}", header, STANDARD.encode(&signature));
+        assert!(legacy.ends_with('='));
+
+        let verifier = JwsVerifier::from_public_key_pem(RSA_PUBLIC_KEY).unwrap();
+        assert!(matches!(jws_error(verifier.verify(&legacy, PAYLOAD.as_bytes())), JwsError::Malformed(_)));
+
+        let verifier = verifier.with_legacy_padding(true);
+        assert!(verifier.verify(&legacy, PAYLOAD.as_bytes()).is_ok());
+    }
+}
@@ -0,0 +1,365 @@
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use crate::client::PolishApiClient;
+use crate::types::{
+    DecoupledAuthError, EatCallbackNotification, EatCodeRequest, PolishApiError, RequestHeaders, Result,
+    TokenRequest, TokenResponse,
+};
+
+/// Shortest delay between token polls
+const MIN_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Driver for the decoupled (External Authorization Tool) authorization flow
+///
+/// Sends `authorizeExt` and then waits for the PSU's decision in their banking
+/// app, either by polling the token endpoint or by waiting for the ASPSP's
+/// callback notification.
+#[derive(Clone)]
+pub struct DecoupledAuthorization<'a> {
+    client: &'a PolishApiClient,
+    poll_interval: Duration,
+    max_poll_interval: Duration,
+    backoff_factor: f64,
+    timeout: Duration,
+}
+
+/// Outcome of a single token poll that did not return a token
+enum PollState {
+    Pending,
+    SlowDown,
+}
+
+impl<'a> DecoupledAuthorization<'a> {
+    /// Create a driver polling every 2 seconds, backing off up to 30 seconds, for at most 5 minutes
+    pub fn new(client: &'a PolishApiClient) -> Self {
+        Self {
+            client,
+            poll_interval: Duration::from_secs(2),
+            max_poll_interval: Duration::from_secs(30),
+            backoff_factor: 1.5,
+            timeout: Duration::from_secs(300),
+        }
+    }
+
+    /// Set the delay before the first poll, at least one second
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval.max(MIN_POLL_INTERVAL);
+        self
+    }
+
+    /// Set the longest delay between polls, at least one second
+    pub fn with_max_poll_interval(mut self, interval: Duration) -> Self {
+        self.max_poll_interval = interval.max(MIN_POLL_INTERVAL);
+        self
+    }
+
+    /// Set the factor the delay grows by after each pending poll (1.0 keeps it fixed)
+    ///
+    /// Factors below 1.0 are raised to 1.0; infinite and NaN factors are ignored.
+    pub fn with_backoff_factor(mut self, factor: f64) -> Self {
+        if factor.is_finite() {
+            self.backoff_factor = factor.max(1.0);
+        } else {
+            tracing::warn!("Ignoring non-finite back-off factor {}", factor);
+        }
+        self
+    }
+
+    /// Set how long to wait for the PSU's decision
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Send `authorizeExt` and poll the token endpoint until the PSU decides
+    ///
+    /// `token_request` is sent on every poll, e.g. with the `exchange_token`
+    /// grant. The ASPSP signals an undecided authorization with the OAuth2
+    /// `authorization_pending` error and asks for slower polling with `slow_down`.
+    pub async fn poll(
+        &self,
+        request: EatCodeRequest,
+        token_request: TokenRequest,
+        headers: RequestHeaders,
+    ) -> Result<TokenResponse> {
+        let deadline = Instant::now() + self.timeout;
+        self.client.auth().authorize_ext(request, headers.clone()).await?;
+
+        let mut interval = self.poll_interval;
+        loop {
+            if Instant::now() + interval > deadline {
+                return Err(DecoupledAuthError::TimedOut(self.timeout).into());
+            }
+            tokio::time::sleep(interval).await;
+
+            let mut poll_headers = headers.clone();
+            poll_headers.x_request_id = Uuid::new_v4();
+            match self.client.auth().token(token_request.clone(), poll_headers).await {
+                Ok(response) => return Ok(response),
+                Err(error) => match classify(error)? {
+                    PollState::Pending => interval = self.next_interval(interval, self.backoff_factor),
+                    PollState::SlowDown => interval = self.next_interval(interval, self.backoff_factor.max(2.0)),
+                },
+            }
+        }
+    }
+
+    /// Grow the delay between polls by `factor`, capped at the longest delay
+    fn next_interval(&self, interval: Duration, factor: f64) -> Duration {
+        let max = self.max_poll_interval.max(MIN_POLL_INTERVAL);
+        let next = (interval.as_secs_f64() * factor).min(max.as_secs_f64());
+        Duration::try_from_secs_f64(next).unwrap_or(max).max(MIN_POLL_INTERVAL)
+    }
+
+    /// Send `authorizeExt` and wait for the callback notification
+    ///
+    /// The application's callback endpoint passes the notification it received
+    /// on `callback`. The authorization code it carries is exchanged for a token
+    /// with the `authorization_code` grant.
+    pub async fn await_callback(
+        &self,
+        request: EatCodeRequest,
+        callback: oneshot::Receiver<EatCallbackNotification>,
+        headers: RequestHeaders,
+    ) -> Result<TokenResponse> {
+        let expected_state = request.state.clone();
+        let client_id = request.client_id.clone();
+        self.client.auth().authorize_ext(request, headers.clone()).await?;
+
+        let notification = match tokio::time::timeout(self.timeout, callback).await {
+            Ok(Ok(notification)) => notification,
+            Ok(Err(_)) => return Err(DecoupledAuthError::CallbackClosed.into()),
+            Err(_) => return Err(DecoupledAuthError::TimedOut(self.timeout).into()),
+        };
+
+        if expected_state.is_some() && notification.state != expected_state {
+            return Err(DecoupledAuthError::StateMismatch.into());
+        }
+        if let Some(error) = notification.error {
+            return Err(rejection(&error, notification.error_description).into());
+        }
+        let code = notification.code.ok_or_else(|| {
+            DecoupledAuthError::Rejected("callback carries neither a code nor an error".to_string())
+        })?;
+
+        let token_request = TokenRequest::authorization_code(client_id, code);
+        let mut token_headers = headers;
+        token_headers.x_request_id = Uuid::new_v4();
+        self.client.auth().token(token_request, token_headers).await
+    }
+}
+
+/// Sort a failed token poll into pending, slow down or a final error
+fn classify(error: PolishApiError) -> Result<PollState> {
+    let code = match &error {
+        PolishApiError::Api { message, .. } => serde_json::from_str::<serde_json::Value>(message)
+            .ok()
+            .and_then(|body| {
+                body.get("error")
+                    .or_else(|| body.get("code"))
+                    .and_then(|code| code.as_str())
+                    .map(str::to_owned)
+            }),
+        _ => None,
+    };
+
+    match code.as_deref() {
+        Some("authorization_pending") => Ok(PollState::Pending),
+        Some("slow_down") => Ok(PollState::SlowDown),
+        Some("expired_token") => Err(DecoupledAuthError::Expired.into()),
+        Some("access_denied") => Err(DecoupledAuthError::Rejected("access_denied".to_string()).into()),
+        _ => Err(error),
+    }
+}
+
+fn rejection(error: &str, description: Option<String>) -> DecoupledAuthError {
+    match (error, description) {
+        ("expired_token", _) => DecoupledAuthError::Expired,
+        (error, Some(description)) => DecoupledAuthError::Rejected(format!("{}: {}", error, description)),
+        (error, None) => DecoupledAuthError::Rejected(error.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, token_body};
+    use crate::types::GrantType;
+
+    const AUTHORIZE_EXT: &str = "/v3_0.1/auth/v3_0.1/authorizeExt";
+    const TOKEN: &str = "/v3_0.1/auth/v3_0.1/token";
+
+    async fn client(server: &mockito::Server) -> PolishApiClient {
+        test_support::client(test_support::config(server)).await
+    }
+
+    fn driver(client: &PolishApiClient) -> DecoupledAuthorization<'_> {
+        client
+            .auth()
+            .decoupled()
+            .with_poll_interval(Duration::from_secs(1))
+            .with_max_poll_interval(Duration::from_secs(1))
+            .with_timeout(Duration::from_secs(10))
+    }
+
+    fn eat_request() -> EatCodeRequest {
+        EatCodeRequest {
+            request_header: Default::default(),
+            response_type: "code".to_string(),
+            eat_code: "123456".to_string(),
+            eat_type: None,
+            client_id: "client".to_string(),
+            scope: "ais".to_string(),
+            scope_details: None,
+            state: Some("state-1".to_string()),
+        }
+    }
+
+    fn exchange_request() -> TokenRequest {
+        TokenRequest {
+            exchange_token: Some("eat-exchange".to_string()),
+            ..TokenRequest::new(GrantType::ExchangeToken, "client")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_poll_until_granted() {
+        let mut server = mockito::Server::new_async().await;
+        let _ext = server.mock("POST", AUTHORIZE_EXT).with_status(204).create_async().await;
+        let pending = server
+            .mock("POST", TOKEN)
+            .with_status(400)
+            .with_body(r#"{"error":"authorization_pending"}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        // Matched once the pending answers are used up
+        let granted = server.mock("POST", TOKEN).with_body(token_body("granted")).create_async().await;
+        let client = client(&server).await;
+
+        let result = driver(&client).poll(eat_request(), exchange_request(), RequestHeaders::default()).await;
+        assert_eq!(result.unwrap().access_token, "granted");
+        pending.assert_async().await;
+        granted.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_poll_intervals_are_bounded() {
+        let server = mockito::Server::new_async().await;
+        let client = client(&server).await;
+        let driver = client
+            .auth()
+            .decoupled()
+            .with_poll_interval(Duration::ZERO)
+            .with_max_poll_interval(Duration::from_secs(30))
+            .with_backoff_factor(f64::INFINITY)
+            .with_backoff_factor(f64::NAN);
+
+        assert_eq!(driver.poll_interval, MIN_POLL_INTERVAL);
+        assert_eq!(driver.backoff_factor, 1.5);
+        assert_eq!(driver.next_interval(Duration::ZERO, 1.5), MIN_POLL_INTERVAL);
+        assert_eq!(driver.next_interval(Duration::from_secs(20), f64::MAX), Duration::from_secs(30));
+        assert_eq!(driver.next_interval(Duration::MAX, 2.0), Duration::from_secs(30));
+    }
+
+    #[tokio::test]
+    async fn test_poll_rejected() {
+        let mut server = mockito::Server::new_async().await;
+        let _ext = server.mock("POST", AUTHORIZE_EXT).with_status(204).create_async().await;
+        let _token = server
+            .mock("POST", TOKEN)
+            .with_status(400)
+            .with_body(r#"{"error":"access_denied"}"#)
+            .create_async()
+            .await;
+        let client = client(&server).await;
+
+        let result = driver(&client).poll(eat_request(), exchange_request(), RequestHeaders::default()).await;
+        assert!(matches!(result, Err(PolishApiError::Decoupled(DecoupledAuthError::Rejected(_)))));
+    }
+
+    #[tokio::test]
+    async fn test_poll_times_out() {
+        let mut server = mockito::Server::new_async().await;
+        let _ext = server.mock("POST", AUTHORIZE_EXT).with_status(204).create_async().await;
+        let _token = server
+            .mock("POST", TOKEN)
+            .with_status(400)
+            .with_body(r#"{"error":"authorization_pending"}"#)
+            .create_async()
+            .await;
+        let client = client(&server).await;
+
+        let result = driver(&client)
+            .with_timeout(Duration::from_millis(100))
+            .poll(eat_request(), exchange_request(), RequestHeaders::default())
+            .await;
+        assert!(matches!(result, Err(PolishApiError::Decoupled(DecoupledAuthError::TimedOut(_)))));
+    }
+
+    #[tokio::test]
+    async fn test_callback_code_is_exchanged() {
+        let mut server = mockito::Server::new_async().await;
+        let _ext = server.mock("POST", AUTHORIZE_EXT).with_status(204).create_async().await;
+        let token = server
+            .mock("POST", TOKEN)
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "grant_type": "authorization_code",
+                "Code": "auth-code"
+            })))
+            .with_body(token_body("granted"))
+            .create_async()
+            .await;
+        let client = client(&server).await;
+
+        let (sender, receiver) = oneshot::channel();
+        sender
+            .send(EatCallbackNotification {
+                code: Some("auth-code".to_string()),
+                state: Some("state-1".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let response = driver(&client).await_callback(eat_request(), receiver, RequestHeaders::default()).await;
+        assert_eq!(response.unwrap().access_token, "granted");
+        token.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_callback_errors() {
+        let mut server = mockito::Server::new_async().await;
+        let _ext = server.mock("POST", AUTHORIZE_EXT).with_status(204).create_async().await;
+        let client = client(&server).await;
+
+        let cases = [
+            (
+                EatCallbackNotification { state: Some("forged".to_string()), ..Default::default() },
+                DecoupledAuthError::StateMismatch,
+            ),
+            (
+                EatCallbackNotification {
+                    state: Some("state-1".to_string()),
+                    error: Some("access_denied".to_string()),
+                    ..Default::default()
+                },
+                DecoupledAuthError::Rejected("access_denied".to_string()),
+            ),
+        ];
+        for (notification, expected) in cases {
+            let (sender, receiver) = oneshot::channel();
+            sender.send(notification).unwrap();
+            let result = driver(&client).await_callback(eat_request(), receiver, RequestHeaders::default()).await;
+            assert!(matches!(result, Err(PolishApiError::Decoupled(error)) if error == expected));
+        }
+
+        let (sender, receiver) = oneshot::channel();
+        drop(sender);
+        let result = driver(&client).await_callback(eat_request(), receiver, RequestHeaders::default()).await;
+        assert!(matches!(result, Err(PolishApiError::Decoupled(DecoupledAuthError::CallbackClosed))));
+    }
+}
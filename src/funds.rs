@@ -1,4 +1,5 @@
 use crate::client::PolishApiClient;
+use crate::profile::Endpoint;
 use crate::types::{
     Result, RequestHeaders,
     FundsConfirmationRequest, FundsConfirmationResponse,
@@ -25,32 +26,19 @@ impl<'a> FundsService<'a> {
     /// Funds confirmation response
     pub async fn confirm_funds(
         &self,
-        request: FundsConfirmationRequest,
+        mut request: FundsConfirmationRequest,
         headers: RequestHeaders,
     ) -> Result<FundsConfirmationResponse> {
-        let payload = serde_json::to_string(&request)?;
-        let signature = self.client.sign_payload(&payload).await?;
-
         let mut request_headers = headers;
-        request_headers.x_jws_signature = signature;
-
-        let builder = self.client.request_builder(
-            reqwest::Method::POST,
-            "/v3_0.1/funds/v3_0.1/confirmation",
-        )?;
-
-        let builder = self.client.add_auth_headers(builder, &request_headers);
+        self.client.prepare_request(&mut request, &mut request_headers);
 
-        let response = builder
-            .body(payload)
-            .send()
-            .await?;
+        let response = self.client.execute(Endpoint::Confirmation, &request, request_headers).await?;
 
         if response.status().is_success() {
-            let funds_response: FundsConfirmationResponse = response.json().await?;
+            let funds_response: FundsConfirmationResponse = self.client.parse_response(response).await?;
             Ok(funds_response)
         } else {
-            let error_text = response.text().await?;
+            let error_text = self.client.error_text(response).await?;
             Err(crate::types::PolishApiError::Api {
                 code: "FUNDS_CONFIRMATION_ERROR".to_string(),
                 message: error_text,
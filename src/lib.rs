@@ -11,6 +11,13 @@
 //! - **Payment Initiation Service (PIS)**: Payment processing
 //! - **Confirmation of Availability of Funds (CAF)**: Funds verification
 //! - **JWS Signature Support**: Request signing using ring cryptography
+//! - **Token Lifecycle**: Stored access tokens refreshed before they expire
+//! - **ASPSP Profiles**: Per-bank URLs, paths and services loaded from TOML or JSON
+//! - **Throttling**: Per-endpoint quotas and the PSD2 limit on unattended AIS calls
+//! - **Middleware**: Hooks around every call for logging, header injection, fault injection or caching
+//! - **Tracing**: A span per call, with optional body logging that masks PSU data and secrets
+//! - **Metrics**: Prometheus/OpenMetrics latency, error, token refresh and throttling metrics (`metrics` feature)
+//! - **Mutual TLS**: QWAC client certificates (PEM or PKCS#12) with custom root CAs
 //! - **Async/Await**: Full async support with tokio
 //! - **Type Safety**: Comprehensive type definitions for all API structures
 //!
@@ -40,16 +47,45 @@ pub mod accounts;
 pub mod payments;
 pub mod funds;
 pub mod client;
+pub mod consent;
+pub mod decoupled;
+pub mod middleware;
+pub mod profile;
+pub mod redaction;
+pub mod registration;
+pub mod retry;
+pub mod throttle;
+pub mod tls;
+pub mod token;
 pub mod types;
 pub mod crypto;
 pub mod utils;
+#[cfg(feature = "loopback")]
+pub mod loopback;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(test)]
+pub(crate) mod test_support;
 
 // Re-export main types for convenience
 pub use client::{PolishApiClient, Config};
+pub use retry::RetryPolicy;
+pub use throttle::{FileQuotaStore, InMemoryQuotaStore, OverQuota, Quota, QuotaSlot, QuotaStore, Throttle};
+pub use tls::ClientIdentity;
+pub use token::{FileTokenStore, InMemoryTokenStore, StoredToken, TokenManager, TokenStore};
 pub use types::errors::{PolishApiError, Result};
 
 // Re-export service modules
-pub use auth::AuthService;
+pub use auth::{AuthService, AuthorizationSession};
+pub use consent::{ConsentRecord, ConsentRegistry, ConsentStatus, ConsentStore, InMemoryConsentStore};
+pub use decoupled::DecoupledAuthorization;
+pub use middleware::{ApiRequest, Middleware, Next};
+pub use profile::{AspspProfile, Endpoint, ProfileRegistry, Service};
+pub use redaction::RedactionPolicy;
+#[cfg(feature = "loopback")]
+pub use loopback::{LoopbackReceiver, RedirectParameters};
+#[cfg(feature = "metrics")]
+pub use metrics::Metrics;
 pub use accounts::AccountService;
 pub use payments::PaymentService;
 pub use funds::FundsService;
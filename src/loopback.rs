@@ -0,0 +1,320 @@
+//! Localhost receiver for the OAuth2 redirect
+//!
+//! Lets CLI tools and tests finish the authorization code flow without a
+//! public web server: register [`LoopbackReceiver::redirect_uri`] as the
+//! redirect URI, open the ASPSP's `aspspRedirectUri` in a browser and wait for
+//! the bank to redirect back.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinSet;
+use url::Url;
+
+use crate::auth::AuthorizationSession;
+use crate::client::PolishApiClient;
+use crate::types::{PolishApiError, RequestHeaders, Result, TokenResponse};
+
+/// How long a connection may take to send its request line and headers
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Most bytes read from a connection before its request is given up on
+const MAX_REQUEST_BYTES: u64 = 8 * 1024;
+
+/// `code` and `state` captured from the redirect
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedirectParameters {
+    pub code: String,
+    pub state: String,
+}
+
+/// HTTP listener on 127.0.0.1 that captures a single OAuth2 redirect
+#[derive(Debug)]
+pub struct LoopbackReceiver {
+    listener: TcpListener,
+    address: SocketAddr,
+    path: String,
+    timeout: Duration,
+}
+
+impl LoopbackReceiver {
+    /// Listen on the given port of 127.0.0.1 (0 picks a free port)
+    pub async fn bind(port: u16) -> Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+        let address = listener.local_addr()?;
+        Ok(Self {
+            listener,
+            address,
+            path: "/callback".to_string(),
+            timeout: Duration::from_secs(300),
+        })
+    }
+
+    /// Set the path the redirect is expected on (default `/callback`)
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Set how long to wait for the redirect
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Address the listener is bound to
+    pub fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Redirect URI to register with the ASPSP and start the session with
+    pub fn redirect_uri(&self) -> String {
+        format!("http://{}{}", self.address, self.path)
+    }
+
+    /// Wait for the redirect and check its `state` against the session
+    ///
+    /// Requests to other paths (e.g. `/favicon.ico`) get a 404 and redirects
+    /// with a different `state` a 400; both are ignored. Connections are read
+    /// concurrently, and one that sends no request within a few seconds is
+    /// dropped, so idle sockets such as a browser's preconnect do not hold up
+    /// the redirect.
+    pub async fn receive(&self, session: &AuthorizationSession) -> Result<RedirectParameters> {
+        tokio::time::timeout(self.timeout, self.accept(session))
+            .await
+            .map_err(|_| PolishApiError::Timeout)?
+    }
+
+    /// Wait for the redirect and exchange its code for a token
+    pub async fn complete(
+        &self,
+        client: &PolishApiClient,
+        session: &AuthorizationSession,
+        headers: RequestHeaders,
+    ) -> Result<TokenResponse> {
+        let redirect = self.receive(session).await?;
+        client.auth().exchange_code(session, &redirect.state, redirect.code, headers).await
+    }
+
+    async fn accept(&self, session: &AuthorizationSession) -> Result<RedirectParameters> {
+        let mut connections = JoinSet::new();
+        loop {
+            tokio::select! {
+                accepted = self.listener.accept() => {
+                    let (mut stream, _) = accepted?;
+                    connections.spawn(async move {
+                        let target = tokio::time::timeout(REQUEST_TIMEOUT, read_request_target(&mut stream)).await;
+                        (stream, target)
+                    });
+                }
+                Some(Ok((mut stream, target))) = connections.join_next() => {
+                    // Connections that time out or send something other than a GET are dropped
+                    let target = match target {
+                        Ok(Ok(target)) => target,
+                        _ => continue,
+                    };
+                    let url = match Url::parse(&format!("http://{}{}", self.address, target)) {
+                        Ok(url) if url.path() == self.path => url,
+                        _ => {
+                            respond(&mut stream, "404 Not Found", "Not found").await;
+                            continue;
+                        }
+                    };
+
+                    let result = match redirect_parameters(&url, session) {
+                        Some(result) => result,
+                        None => {
+                            tracing::warn!("Ignoring redirect whose state does not match the session");
+                            respond(&mut stream, "400 Bad Request", "Unknown authorization request.").await;
+                            continue;
+                        }
+                    };
+                    match &result {
+                        Ok(_) => respond(&mut stream, "200 OK", "Authorization complete. You can close this window.").await,
+                        Err(_) => respond(&mut stream, "400 Bad Request", "Authorization failed. You can close this window.").await,
+                    }
+                    return result;
+                }
+            }
+        }
+    }
+}
+
+/// Extract and check the redirect's query parameters
+///
+/// Returns `None` if the `state` does not match the session; the redirect
+/// then belongs to another flow (or another process) and its `error` is not
+/// acted on.
+fn redirect_parameters(url: &Url, session: &AuthorizationSession) -> Option<Result<RedirectParameters>> {
+    let parameter = |name: &str| url.query_pairs().find(|(key, _)| key == name).map(|(_, value)| value.into_owned());
+
+    let state = parameter("state").unwrap_or_default();
+    session.validate_state(&state).ok()?;
+
+    if let Some(error) = parameter("error") {
+        let message = match parameter("error_description") {
+            Some(description) => format!("{}: {}", error, description),
+            None => error,
+        };
+        return Some(Err(PolishApiError::Authorization { message }));
+    }
+
+    Some(
+        parameter("code")
+            .map(|code| RedirectParameters { code, state })
+            .ok_or_else(|| PolishApiError::Authorization {
+                message: "redirect carries no authorization code".to_string(),
+            }),
+    )
+}
+
+/// Read the request line and headers, returning the request target
+///
+/// At most [`MAX_REQUEST_BYTES`] are read; a longer request is rejected.
+async fn read_request_target(stream: &mut TcpStream) -> std::io::Result<String> {
+    let unsupported = || std::io::Error::new(std::io::ErrorKind::InvalidData, "unsupported request");
+    let mut reader = BufReader::new(stream.take(MAX_REQUEST_BYTES));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    if !request_line.ends_with('\n') {
+        return Err(unsupported());
+    }
+
+    let mut header = String::new();
+    loop {
+        header.clear();
+        if reader.read_line(&mut header).await? == 0 || !header.ends_with('\n') {
+            return Err(unsupported());
+        }
+        if header == "\r\n" || header == "\n" {
+            break;
+        }
+    }
+
+    match request_line.split_whitespace().collect::<Vec<_>>()[..] {
+        ["GET", target, _] => Ok(target.to_string()),
+        _ => Err(unsupported()),
+    }
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, message: &str) {
+    let body = format!("<!DOCTYPE html><html><body><p>{}</p></body></html>", message);
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    // The browser may already be gone; the redirect has been captured either way
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, response_body, token_body};
+
+    async fn client(server: &mockito::Server) -> PolishApiClient {
+        test_support::client(test_support::config(server)).await
+    }
+
+    /// Follows redirects like the PSU's browser would
+    fn browser() -> reqwest::Client {
+        reqwest::Client::builder().build().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_fake_bank_redirect_is_exchanged() {
+        let receiver = LoopbackReceiver::bind(0).await.unwrap().with_timeout(Duration::from_secs(5));
+        let session = AuthorizationSession::new(receiver.redirect_uri()).unwrap();
+
+        let mut bank = mockito::Server::new_async().await;
+        let _authorize = bank
+            .mock("POST", "/v3_0.1/auth/v3_0.1/authorize")
+            .with_body(response_body(&format!(r#""aspspRedirectUri":"{}/login""#, bank.url())))
+            .create_async()
+            .await;
+        let _login = bank
+            .mock("GET", "/login")
+            .with_status(302)
+            .with_header("Location", &format!("{}?code=auth-code&state={}", receiver.redirect_uri(), session.state))
+            .create_async()
+            .await;
+        let token = bank
+            .mock("POST", "/v3_0.1/auth/v3_0.1/token")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "Code": "auth-code",
+                "code_verifier": session.code_verifier,
+                "redirect_uri": receiver.redirect_uri()
+            })))
+            .with_body(token_body("granted"))
+            .create_async()
+            .await;
+
+        let client = client(&bank).await;
+        let authorize = client
+            .auth()
+            .authorize(session.authorize_request("client", "ais"), RequestHeaders::default())
+            .await
+            .unwrap();
+
+        let psu = async {
+            let page = browser().get(&authorize.aspsp_redirect_uri).send().await.unwrap();
+            assert_eq!(page.status(), 200);
+        };
+        let (response, _) = tokio::join!(receiver.complete(&client, &session, RequestHeaders::default()), psu);
+
+        assert_eq!(response.unwrap().access_token, "granted");
+        token.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_forged_redirects_are_ignored() {
+        let receiver = LoopbackReceiver::bind(0).await.unwrap().with_timeout(Duration::from_secs(5));
+        let session = AuthorizationSession::new(receiver.redirect_uri()).unwrap();
+        let forged_code = format!("{}?code=forged-code&state=forged", receiver.redirect_uri());
+        let forged_error = format!("{}?error=access_denied&state=forged", receiver.redirect_uri());
+        let genuine = format!("{}?code=auth-code&state={}", receiver.redirect_uri(), session.state);
+
+        let psu = async {
+            // Unrelated requests and redirects of other flows are ignored
+            let favicon = format!("http://{}/favicon.ico", receiver.local_addr());
+            assert_eq!(browser().get(&favicon).send().await.unwrap().status(), 404);
+            assert_eq!(browser().get(&forged_code).send().await.unwrap().status(), 400);
+            assert_eq!(browser().get(&forged_error).send().await.unwrap().status(), 400);
+            assert_eq!(browser().get(&genuine).send().await.unwrap().status(), 200);
+        };
+        let (result, _) = tokio::join!(receiver.receive(&session), psu);
+
+        assert_eq!(result.unwrap(), RedirectParameters { code: "auth-code".to_string(), state: session.state.clone() });
+    }
+
+    #[tokio::test]
+    async fn test_idle_connection_does_not_block_redirect() {
+        let receiver = LoopbackReceiver::bind(0).await.unwrap().with_timeout(Duration::from_secs(2));
+        let session = AuthorizationSession::new(receiver.redirect_uri()).unwrap();
+        let genuine = format!("{}?code=auth-code&state={}", receiver.redirect_uri(), session.state);
+
+        // Like a browser's preconnect: opened first, never sends a request
+        let _idle = TcpStream::connect(receiver.local_addr()).await.unwrap();
+        let (result, _) = tokio::join!(receiver.receive(&session), browser().get(&genuine).send());
+
+        assert_eq!(result.unwrap().code, "auth-code");
+    }
+
+    #[tokio::test]
+    async fn test_error_redirect_and_timeout() {
+        let receiver = LoopbackReceiver::bind(0).await.unwrap().with_timeout(Duration::from_secs(5));
+        let session = AuthorizationSession::new(receiver.redirect_uri()).unwrap();
+        let denied = format!("{}?error=access_denied&state={}", receiver.redirect_uri(), session.state);
+
+        let (result, _) = tokio::join!(receiver.receive(&session), browser().get(&denied).send());
+        assert!(matches!(result, Err(PolishApiError::Authorization { message }) if message == "access_denied"));
+
+        let receiver = receiver.with_timeout(Duration::from_millis(50));
+        assert!(matches!(receiver.receive(&session).await, Err(PolishApiError::Timeout)));
+    }
+}
@@ -0,0 +1,280 @@
+//! Prometheus/OpenMetrics metrics for API calls
+//!
+//! Attach [`Metrics`] to a client with [`PolishApiClient::with_metrics`] and
+//! render its registry in the OpenMetrics text format from your `/metrics`
+//! handler. Recorded per bank (profile ID, or host of the base URL):
+//!
+//! - `polishapi_request_duration_seconds`: latency histogram of the requests sent,
+//!   by service and endpoint
+//! - `polishapi_requests_total`: requests sent by endpoint and HTTP status (`error` if no response)
+//! - `polishapi_errors_total`: failed calls by endpoint and [`PolishApiError`] variant, from
+//!   validating the request through the middleware chain to parsing the response;
+//!   unsuccessful HTTP statuses count as `api`
+//! - `polishapi_token_refreshes_total`: access token refreshes by outcome
+//! - `polishapi_throttle_rejections_total`: calls rejected by the client-side throttle;
+//!   these are never sent and are counted nowhere else
+//!
+//! [`PolishApiClient::with_metrics`]: crate::PolishApiClient::with_metrics
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use prometheus_client::encoding::text::encode;
+use prometheus_client::encoding::EncodeLabelSet;
+use prometheus_client::metrics::counter::Counter;
+use prometheus_client::metrics::family::Family;
+use prometheus_client::metrics::histogram::{exponential_buckets, Histogram};
+use prometheus_client::registry::Registry;
+use reqwest::Response;
+
+use crate::profile::Endpoint;
+use crate::types::{PolishApiError, Result};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+struct CallLabels {
+    bank: String,
+    service: String,
+    endpoint: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+struct RequestLabels {
+    bank: String,
+    endpoint: String,
+    status: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+struct ErrorLabels {
+    bank: String,
+    endpoint: String,
+    error: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+struct RefreshLabels {
+    bank: String,
+    outcome: String,
+}
+
+/// Latency buckets from 10 ms to about 20 s
+fn duration_histogram() -> Histogram {
+    Histogram::new(exponential_buckets(0.01, 2.0, 12))
+}
+
+struct Inner {
+    registry: Registry,
+    request_duration: Family<CallLabels, Histogram, fn() -> Histogram>,
+    requests: Family<RequestLabels, Counter>,
+    errors: Family<ErrorLabels, Counter>,
+    token_refreshes: Family<RefreshLabels, Counter>,
+    throttle_rejections: Family<CallLabels, Counter>,
+}
+
+/// Metrics recorded by a client, with the registry they are exposed in
+///
+/// Cloning is cheap; clones share the same metrics, so one instance can be
+/// attached to the clients of several banks.
+#[derive(Clone)]
+pub struct Metrics {
+    inner: Arc<Inner>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        let mut registry = Registry::with_prefix("polishapi");
+        let request_duration = Family::<CallLabels, Histogram, fn() -> Histogram>::new_with_constructor(duration_histogram);
+        let requests = Family::<RequestLabels, Counter>::default();
+        let errors = Family::<ErrorLabels, Counter>::default();
+        let token_refreshes = Family::<RefreshLabels, Counter>::default();
+        let throttle_rejections = Family::<CallLabels, Counter>::default();
+
+        registry.register("request_duration_seconds", "Latency of PolishAPI calls", request_duration.clone());
+        registry.register("requests", "PolishAPI calls by HTTP status", requests.clone());
+        registry.register("errors", "Failed PolishAPI calls by error kind", errors.clone());
+        registry.register("token_refreshes", "Access token refreshes", token_refreshes.clone());
+        registry.register(
+            "throttle_rejections",
+            "Calls rejected by the client-side throttle",
+            throttle_rejections.clone(),
+        );
+
+        Self {
+            inner: Arc::new(Inner {
+                registry,
+                request_duration,
+                requests,
+                errors,
+                token_refreshes,
+                throttle_rejections,
+            }),
+        }
+    }
+}
+
+impl Metrics {
+    /// Create metrics in a new registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry holding the metrics
+    pub fn registry(&self) -> &Registry {
+        &self.inner.registry
+    }
+
+    /// Render the registry in the OpenMetrics text format
+    pub fn encode(&self) -> Result<String> {
+        let mut output = String::new();
+        encode(&mut output, &self.inner.registry)
+            .map_err(|e| PolishApiError::Internal(format!("Failed to encode metrics: {}", e)))?;
+        Ok(output)
+    }
+
+    /// Record a request handed to the HTTP client, with its status (`error` if no response)
+    pub(crate) fn record_request(&self, bank: &str, endpoint: Endpoint, latency: Duration, result: &Result<Response>) {
+        let status = match result {
+            Ok(response) => response.status().as_str().to_string(),
+            Err(_) => "error".to_string(),
+        };
+        let call = CallLabels {
+            bank: bank.to_string(),
+            service: endpoint.service().to_string(),
+            endpoint: endpoint.to_string(),
+        };
+        self.inner.request_duration.get_or_create(&call).observe(latency.as_secs_f64());
+        self.inner
+            .requests
+            .get_or_create(&RequestLabels { bank: call.bank, endpoint: call.endpoint, status })
+            .inc();
+    }
+
+    /// Record a failed call by error kind (see [`PolishApiError::kind`])
+    pub(crate) fn record_error(&self, bank: &str, endpoint: Endpoint, kind: &str) {
+        self.inner
+            .errors
+            .get_or_create(&ErrorLabels {
+                bank: bank.to_string(),
+                endpoint: endpoint.to_string(),
+                error: kind.to_string(),
+            })
+            .inc();
+    }
+
+    /// Record a call rejected by the client-side throttle
+    pub(crate) fn record_throttle_rejection(&self, bank: &str, endpoint: Endpoint) {
+        self.inner
+            .throttle_rejections
+            .get_or_create(&CallLabels {
+                bank: bank.to_string(),
+                service: endpoint.service().to_string(),
+                endpoint: endpoint.to_string(),
+            })
+            .inc();
+    }
+
+    /// Record an access token refresh
+    pub(crate) fn record_token_refresh(&self, bank: &str, success: bool) {
+        let outcome = if success { "success" } else { "failure" };
+        self.inner
+            .token_refreshes
+            .get_or_create(&RefreshLabels { bank: bank.to_string(), outcome: outcome.to_string() })
+            .inc();
+    }
+}
+
+impl std::fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Metrics").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, response_body, token_body};
+    use crate::types::{GetAccountsRequest, GrantType, RequestHeaders, TokenRequest};
+    use crate::{InMemoryQuotaStore, PolishApiClient, InMemoryTokenStore, Quota, StoredToken, Throttle, TokenManager, TokenStore};
+
+    const GET_ACCOUNTS: &str = "/v3_0.1/accounts/v3_0.1/getAccounts";
+
+    fn request() -> GetAccountsRequest {
+        GetAccountsRequest { request_header: Default::default(), page_id: None, per_page: None }
+    }
+
+    #[tokio::test]
+    async fn test_calls_are_recorded() {
+        let mut server = mockito::Server::new_async().await;
+        let accounts = server
+            .mock("POST", GET_ACCOUNTS)
+            .with_body(response_body(r#""accounts":[]"#))
+            .create_async()
+            .await;
+        let _token = server
+            .mock("POST", "/v3_0.1/auth/v3_0.1/token")
+            .with_body(token_body("fresh"))
+            .create_async()
+            .await;
+
+        let store = Arc::new(InMemoryTokenStore::new());
+        store
+            .save(&StoredToken {
+                access_token: "stale".to_string(),
+                token_type: "Bearer".to_string(),
+                refresh_token: Some("refresh".to_string()),
+                scope: "ais".to_string(),
+                expires_at: chrono::Utc::now(),
+                consent_id: None,
+            })
+            .await
+            .unwrap();
+        let throttle = Throttle::new(Arc::new(InMemoryQuotaStore::new()))
+            .with_quota(Endpoint::GetAccounts, Quota::new(3, Duration::from_secs(3600)));
+        let metrics = Metrics::new();
+        let client = test_support::client(test_support::config(&server))
+            .await
+            .with_token_manager(TokenManager::new(store))
+            .with_throttle(throttle)
+            .with_metrics(metrics.clone());
+
+        client.accounts().get_accounts(request(), RequestHeaders::default()).await.unwrap();
+        accounts.remove_async().await;
+        let failure = server.mock("POST", GET_ACCOUNTS).with_status(500).create_async().await;
+        assert!(client.accounts().get_accounts(request(), RequestHeaders::default()).await.is_err());
+        failure.remove_async().await;
+        let _garbled = server.mock("POST", GET_ACCOUNTS).with_body("not json").create_async().await;
+        let result = client.accounts().get_accounts(request(), RequestHeaders::default()).await;
+        assert!(matches!(result, Err(PolishApiError::Json(_))));
+        let result = client.accounts().get_accounts(request(), RequestHeaders::default()).await;
+        assert!(matches!(result, Err(PolishApiError::QuotaExceeded { .. })));
+        let result = client
+            .auth()
+            .token(TokenRequest::new(GrantType::AuthorizationCode, "client"), RequestHeaders::default())
+            .await;
+        assert!(matches!(result, Err(PolishApiError::Validation(_))));
+        // Signing fails before the request is sent
+        let unsigned = PolishApiClient::new(test_support::config(&server)).await.unwrap().with_metrics(metrics.clone());
+        let result = unsigned.accounts().get_accounts(request(), RequestHeaders::default()).await;
+        assert!(matches!(result, Err(PolishApiError::Config(_))));
+
+        let output = metrics.encode().unwrap();
+        for line in [
+            r#"polishapi_requests_total{bank="127.0.0.1",endpoint="getAccounts",status="200"} 2"#,
+            r#"polishapi_requests_total{bank="127.0.0.1",endpoint="getAccounts",status="500"} 1"#,
+            r#"polishapi_requests_total{bank="127.0.0.1",endpoint="token",status="200"} 1"#,
+            r#"polishapi_errors_total{bank="127.0.0.1",endpoint="getAccounts",error="api"} 1"#,
+            r#"polishapi_errors_total{bank="127.0.0.1",endpoint="getAccounts",error="json"} 1"#,
+            r#"polishapi_errors_total{bank="127.0.0.1",endpoint="token",error="validation"} 1"#,
+            r#"polishapi_errors_total{bank="127.0.0.1",endpoint="getAccounts",error="config"} 1"#,
+            r#"polishapi_token_refreshes_total{bank="127.0.0.1",outcome="success"} 1"#,
+            r#"polishapi_throttle_rejections_total{bank="127.0.0.1",service="AIS",endpoint="getAccounts"} 1"#,
+            r#"polishapi_request_duration_seconds_count{bank="127.0.0.1",service="AIS",endpoint="getAccounts"} 3"#,
+        ] {
+            assert!(output.contains(line), "missing {} in\n{}", line, output);
+        }
+        // Rejected and unsigned calls were never sent
+        assert!(!output.contains(r#"status="error""#), "{}", output);
+        assert!(!output.contains("quota_exceeded"), "{}", output);
+        assert!(output.ends_with("# EOF\n"));
+    }
+}
@@ -0,0 +1,223 @@
+//! Request/response middleware
+//!
+//! Every call made by the service methods runs through a chain of
+//! [`Middleware`]s before it is sent. Middlewares added with
+//! [`PolishApiClient::with_middleware`] run first, in the order they were
+//! added, followed by the built-in ones: [`AccessToken`], [`Throttling`] and
+//! [`JwsSignature`]. The end of the chain builds the HTTP request and sends it
+//! according to the retry policy.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use reqwest::header::HeaderMap;
+use reqwest::Response;
+use serde_json::Value;
+
+use crate::client::PolishApiClient;
+use crate::profile::{Endpoint, Service};
+use crate::types::{RequestHeaders, Result};
+
+/// A call on its way through the middleware chain
+#[derive(Debug, Clone)]
+pub struct ApiRequest {
+    pub endpoint: Endpoint,
+    pub headers: RequestHeaders,
+    /// Additional HTTP headers sent with the request
+    pub extra_headers: HeaderMap,
+    /// Request body; changes made after [`JwsSignature`] invalidate the signature
+    pub body: Value,
+}
+
+/// Hook around every call made by the client
+///
+/// Implementations can inspect or change the request, call `next.run` to pass
+/// it on and inspect the response, or answer without calling `next` at all.
+#[async_trait]
+pub trait Middleware: Send + Sync {
+    async fn handle(&self, request: ApiRequest, next: Next<'_>) -> Result<Response>;
+}
+
+/// The rest of the middleware chain
+pub struct Next<'a> {
+    client: &'a PolishApiClient,
+    chain: &'a [Arc<dyn Middleware>],
+}
+
+impl<'a> Next<'a> {
+    pub(crate) fn new(client: &'a PolishApiClient, chain: &'a [Arc<dyn Middleware>]) -> Self {
+        Self { client, chain }
+    }
+
+    /// Client the call is made with
+    pub fn client(&self) -> &'a PolishApiClient {
+        self.client
+    }
+
+    /// Pass the request to the next middleware, or send it if this is the end of the chain
+    pub async fn run(self, request: ApiRequest) -> Result<Response> {
+        match self.chain.split_first() {
+            Some((middleware, rest)) => middleware.handle(request, Next::new(self.client, rest)).await,
+            None => self.client.transport(request).await,
+        }
+    }
+}
+
+/// Built-in middleware setting the bearer token from the token manager
+///
+/// AIS, PIS and CAF calls without an `Authorization` header get the managed
+/// access token, which is also put into an empty `requestHeader.token`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AccessToken;
+
+#[async_trait]
+impl Middleware for AccessToken {
+    async fn handle(&self, mut request: ApiRequest, next: Next<'_>) -> Result<Response> {
+        if request.endpoint.service() != Service::Auth {
+            next.client().apply_access_token(&mut request.headers).await?;
+
+            let authorization = &request.headers.authorization;
+            let token = authorization.strip_prefix("Bearer ").unwrap_or(authorization);
+            if let Some(Value::String(body_token)) = request.body.pointer_mut("/requestHeader/token") {
+                if body_token.is_empty() {
+                    *body_token = token.to_string();
+                }
+            }
+        }
+        next.run(request).await
+    }
+}
+
+/// Built-in middleware counting calls against the client's throttle
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Throttling;
+
+#[async_trait]
+impl Middleware for Throttling {
+    async fn handle(&self, request: ApiRequest, next: Next<'_>) -> Result<Response> {
+        if request.endpoint.service() != Service::Auth {
+            let account_number = request.body.get("accountNumber").and_then(Value::as_str);
+            next.client().acquire_quota(request.endpoint, &request.headers, account_number).await?;
+        }
+        next.run(request).await
+    }
+}
+
+/// Built-in middleware signing the body into the `X-JWS-SIGNATURE` header
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JwsSignature;
+
+#[async_trait]
+impl Middleware for JwsSignature {
+    async fn handle(&self, mut request: ApiRequest, next: Next<'_>) -> Result<Response> {
+        let payload = serde_json::to_string(&request.body)?;
+        request.headers.x_jws_signature = next.client().sign_payload(&payload).await?;
+        next.run(request).await
+    }
+}
+
+/// Number of built-in middlewares, which end every chain
+pub(crate) const BUILT_IN_COUNT: usize = 3;
+
+/// Built-in middlewares, in the order they run
+pub(crate) fn built_in() -> [Arc<dyn Middleware>; BUILT_IN_COUNT] {
+    [Arc::new(AccessToken), Arc::new(Throttling), Arc::new(JwsSignature)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::JwsVerifier;
+    use crate::test_support::{self, response_body};
+    use crate::types::{GetAccountsRequest, PolishApiError};
+    use crate::utils::HeadersBuilder;
+    use std::sync::Mutex;
+
+    const GET_ACCOUNTS: &str = "/v3_0.1/accounts/v3_0.1/getAccounts";
+
+    /// Records the calls it sees and tags them with a header and a body field
+    struct Recorder {
+        name: &'static str,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl Middleware for Recorder {
+        async fn handle(&self, mut request: ApiRequest, next: Next<'_>) -> Result<Response> {
+            self.log.lock().unwrap().push(format!("{} {}", self.name, request.endpoint));
+            request.extra_headers.insert("X-Middleware", self.name.parse().unwrap());
+            request.body["requestHeader"]["userAgent"] = Value::from(self.name);
+            let response = next.run(request).await?;
+            self.log.lock().unwrap().push(format!("{} {}", self.name, response.status().as_u16()));
+            Ok(response)
+        }
+    }
+
+    /// Fails every call without sending it
+    struct Outage;
+
+    #[async_trait]
+    impl Middleware for Outage {
+        async fn handle(&self, _request: ApiRequest, _next: Next<'_>) -> Result<Response> {
+            Err(PolishApiError::Timeout)
+        }
+    }
+
+    async fn client(server: &mockito::Server) -> PolishApiClient {
+        test_support::client(test_support::config(server)).await
+    }
+
+    fn request() -> GetAccountsRequest {
+        GetAccountsRequest { request_header: Default::default(), page_id: None, per_page: None }
+    }
+
+    #[tokio::test]
+    async fn test_middlewares_wrap_the_call() {
+        let mut server = mockito::Server::new_async().await;
+        let captured = Arc::new(Mutex::new((String::new(), Vec::new())));
+        let capture = captured.clone();
+        let mock = server
+            .mock("POST", GET_ACCOUNTS)
+            .match_header("X-Middleware", "inner")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "requestHeader": { "userAgent": "inner", "token": "token" }
+            })))
+            .with_body_from_request(move |request| {
+                let signature = request.header("X-JWS-SIGNATURE")[0].to_str().unwrap().to_string();
+                *capture.lock().unwrap() = (signature, request.body().unwrap().clone());
+                response_body(r#""accounts":[]"#).into()
+            })
+            .create_async()
+            .await;
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let client = client(&server)
+            .await
+            .with_middleware(Arc::new(Recorder { name: "outer", log: log.clone() }))
+            .with_middleware(Arc::new(Recorder { name: "inner", log: log.clone() }));
+        let headers = HeadersBuilder::new().authorization("token").build();
+        client.accounts().get_accounts(request(), headers).await.unwrap();
+        mock.assert_async().await;
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            ["outer getAccounts", "inner getAccounts", "inner 200", "outer 200"]
+        );
+
+        // Changes made by middlewares are covered by the signature
+        let (signature, body) = captured.lock().unwrap().clone();
+        let verifier = JwsVerifier::from_certificate_pem(include_str!("../testdata/rsa_certificate.pem")).unwrap();
+        assert!(verifier.verify(&signature, &body).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_middleware_can_answer_without_sending() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server.mock("POST", GET_ACCOUNTS).expect(0).create_async().await;
+
+        let client = client(&server).await.with_middleware(Arc::new(Outage));
+        let result = client.accounts().get_accounts(request(), RequestHeaders::default()).await;
+        assert!(matches!(result, Err(PolishApiError::Timeout)));
+        mock.assert_async().await;
+    }
+}
@@ -1,9 +1,9 @@
 use crate::client::PolishApiClient;
+use crate::profile::Endpoint;
 use crate::types::{
     Result, RequestHeaders,
     DomesticPaymentRequest, EeaPaymentRequest, NonEeaPaymentRequest, TaxPaymentRequest,
     PaymentInitiationResponse, PaymentStatusRequest, PaymentStatusResponse,
-    PaymentInformationRequest, PaymentInformationResponse,
 };
 
 /// Payment Initiation Service implementation
@@ -27,32 +27,19 @@ impl<'a> PaymentService<'a> {
     /// Payment initiation response
     pub async fn initiate_domestic_payment(
         &self,
-        request: DomesticPaymentRequest,
+        mut request: DomesticPaymentRequest,
         headers: RequestHeaders,
     ) -> Result<PaymentInitiationResponse> {
-        let payload = serde_json::to_string(&request)?;
-        let signature = self.client.sign_payload(&payload).await?;
-
         let mut request_headers = headers;
-        request_headers.x_jws_signature = signature;
-
-        let builder = self.client.request_builder(
-            reqwest::Method::POST,
-            "/v3_0.1/payments/v3_0.1/domestic",
-        )?;
-
-        let builder = self.client.add_auth_headers(builder, &request_headers);
+        self.client.prepare_request(&mut request, &mut request_headers);
 
-        let response = builder
-            .body(payload)
-            .send()
-            .await?;
+        let response = self.client.execute(Endpoint::Domestic, &request, request_headers).await?;
 
         if response.status().is_success() {
-            let payment_response: PaymentInitiationResponse = response.json().await?;
+            let payment_response: PaymentInitiationResponse = self.client.parse_response(response).await?;
             Ok(payment_response)
         } else {
-            let error_text = response.text().await?;
+            let error_text = self.client.error_text(response).await?;
             Err(crate::types::PolishApiError::Api {
                 code: "DOMESTIC_PAYMENT_ERROR".to_string(),
                 message: error_text,
@@ -70,32 +57,19 @@ impl<'a> PaymentService<'a> {
     /// Payment initiation response
     pub async fn initiate_eea_payment(
         &self,
-        request: EeaPaymentRequest,
+        mut request: EeaPaymentRequest,
         headers: RequestHeaders,
     ) -> Result<PaymentInitiationResponse> {
-        let payload = serde_json::to_string(&request)?;
-        let signature = self.client.sign_payload(&payload).await?;
-
         let mut request_headers = headers;
-        request_headers.x_jws_signature = signature;
-
-        let builder = self.client.request_builder(
-            reqwest::Method::POST,
-            "/v3_0.1/payments/v3_0.1/EEA",
-        )?;
+        self.client.prepare_request(&mut request, &mut request_headers);
 
-        let builder = self.client.add_auth_headers(builder, &request_headers);
-
-        let response = builder
-            .body(payload)
-            .send()
-            .await?;
+        let response = self.client.execute(Endpoint::Eea, &request, request_headers).await?;
 
         if response.status().is_success() {
-            let payment_response: PaymentInitiationResponse = response.json().await?;
+            let payment_response: PaymentInitiationResponse = self.client.parse_response(response).await?;
             Ok(payment_response)
         } else {
-            let error_text = response.text().await?;
+            let error_text = self.client.error_text(response).await?;
             Err(crate::types::PolishApiError::Api {
                 code: "EEA_PAYMENT_ERROR".to_string(),
                 message: error_text,
@@ -113,32 +87,19 @@ impl<'a> PaymentService<'a> {
     /// Payment initiation response
     pub async fn initiate_non_eea_payment(
         &self,
-        request: NonEeaPaymentRequest,
+        mut request: NonEeaPaymentRequest,
         headers: RequestHeaders,
     ) -> Result<PaymentInitiationResponse> {
-        let payload = serde_json::to_string(&request)?;
-        let signature = self.client.sign_payload(&payload).await?;
-
         let mut request_headers = headers;
-        request_headers.x_jws_signature = signature;
-
-        let builder = self.client.request_builder(
-            reqwest::Method::POST,
-            "/v3_0.1/payments/v3_0.1/nonEEA",
-        )?;
-
-        let builder = self.client.add_auth_headers(builder, &request_headers);
+        self.client.prepare_request(&mut request, &mut request_headers);
 
-        let response = builder
-            .body(payload)
-            .send()
-            .await?;
+        let response = self.client.execute(Endpoint::NonEea, &request, request_headers).await?;
 
         if response.status().is_success() {
-            let payment_response: PaymentInitiationResponse = response.json().await?;
+            let payment_response: PaymentInitiationResponse = self.client.parse_response(response).await?;
             Ok(payment_response)
         } else {
-            let error_text = response.text().await?;
+            let error_text = self.client.error_text(response).await?;
             Err(crate::types::PolishApiError::Api {
                 code: "NON_EEA_PAYMENT_ERROR".to_string(),
                 message: error_text,
@@ -156,32 +117,19 @@ impl<'a> PaymentService<'a> {
     /// Payment initiation response
     pub async fn initiate_tax_payment(
         &self,
-        request: TaxPaymentRequest,
+        mut request: TaxPaymentRequest,
         headers: RequestHeaders,
     ) -> Result<PaymentInitiationResponse> {
-        let payload = serde_json::to_string(&request)?;
-        let signature = self.client.sign_payload(&payload).await?;
-
         let mut request_headers = headers;
-        request_headers.x_jws_signature = signature;
-
-        let builder = self.client.request_builder(
-            reqwest::Method::POST,
-            "/v3_0.1/payments/v3_0.1/tax",
-        )?;
-
-        let builder = self.client.add_auth_headers(builder, &request_headers);
+        self.client.prepare_request(&mut request, &mut request_headers);
 
-        let response = builder
-            .body(payload)
-            .send()
-            .await?;
+        let response = self.client.execute(Endpoint::Tax, &request, request_headers).await?;
 
         if response.status().is_success() {
-            let payment_response: PaymentInitiationResponse = response.json().await?;
+            let payment_response: PaymentInitiationResponse = self.client.parse_response(response).await?;
             Ok(payment_response)
         } else {
-            let error_text = response.text().await?;
+            let error_text = self.client.error_text(response).await?;
             Err(crate::types::PolishApiError::Api {
                 code: "TAX_PAYMENT_ERROR".to_string(),
                 message: error_text,
@@ -189,7 +137,7 @@ impl<'a> PaymentService<'a> {
         }
     }
 
-    /// Get payment status
+    /// Get payment status (getPayment)
     ///
     /// # Arguments
     /// * `request` - Payment status request parameters
@@ -199,32 +147,19 @@ impl<'a> PaymentService<'a> {
     /// Payment status response
     pub async fn get_payment_status(
         &self,
-        request: PaymentStatusRequest,
+        mut request: PaymentStatusRequest,
         headers: RequestHeaders,
     ) -> Result<PaymentStatusResponse> {
-        let payload = serde_json::to_string(&request)?;
-        let signature = self.client.sign_payload(&payload).await?;
-
         let mut request_headers = headers;
-        request_headers.x_jws_signature = signature;
+        self.client.prepare_request(&mut request, &mut request_headers);
 
-        let builder = self.client.request_builder(
-            reqwest::Method::POST,
-            "/v3_0.1/payments/v3_0.1/status",
-        )?;
-
-        let builder = self.client.add_auth_headers(builder, &request_headers);
-
-        let response = builder
-            .body(payload)
-            .send()
-            .await?;
+        let response = self.client.execute(Endpoint::GetPayment, &request, request_headers).await?;
 
         if response.status().is_success() {
-            let status_response: PaymentStatusResponse = response.json().await?;
+            let status_response: PaymentStatusResponse = self.client.parse_response(response).await?;
             Ok(status_response)
         } else {
-            let error_text = response.text().await?;
+            let error_text = self.client.error_text(response).await?;
             Err(crate::types::PolishApiError::Api {
                 code: "PAYMENT_STATUS_ERROR".to_string(),
                 message: error_text,
@@ -234,45 +169,14 @@ impl<'a> PaymentService<'a> {
 
     /// Get payment information
     ///
-    /// # Arguments
-    /// * `request` - Payment information request parameters
-    /// * `headers` - Request headers including authentication
-    ///
-    /// # Returns
-    /// Payment information response
+    /// PolishAPI 3.0 has no separate information endpoint; this calls
+    /// `getPayment`, which returns the payment status and details.
+    #[deprecated(since = "0.2.0", note = "use `get_payment_status` (getPayment) instead")]
     pub async fn get_payment_information(
         &self,
-        request: PaymentInformationRequest,
+        request: PaymentStatusRequest,
         headers: RequestHeaders,
-    ) -> Result<PaymentInformationResponse> {
-        let payload = serde_json::to_string(&request)?;
-        let signature = self.client.sign_payload(&payload).await?;
-
-        let mut request_headers = headers;
-        request_headers.x_jws_signature = signature;
-
-        let builder = self.client.request_builder(
-            reqwest::Method::POST,
-            "/v3_0.1/payments/v3_0.1/information",
-        )?;
-
-        let builder = self.client.add_auth_headers(builder, &request_headers);
-
-        let response = builder
-            .body(payload)
-            .send()
-            .await?;
-
-        if response.status().is_success() {
-            let info_response: PaymentInformationResponse = response.json().await?;
-            Ok(info_response)
-        } else {
-            let error_text = response.text().await?;
-            Err(crate::types::PolishApiError::Api {
-                code: "PAYMENT_INFO_ERROR".to_string(),
-                message: error_text,
-            })
-        }
+    ) -> Result<PaymentStatusResponse> {
+        self.get_payment_status(request, headers).await
     }
 }
-
@@ -0,0 +1,425 @@
+//! Per-bank (ASPSP) profiles
+//!
+//! Polish banks differ in base URLs, path prefixes, API versions, client
+//! authentication and the services they offer. An [`AspspProfile`] describes
+//! one bank; a [`ProfileRegistry`] loads many of them from a TOML or JSON file
+//! and builds a [`Config`] that routes every call according to the profile.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::client::Config;
+use crate::tls::ClientIdentity;
+use crate::types::{ClientAuthMethod, PolishApiError, Result};
+
+/// API version used when a profile does not name one
+pub const DEFAULT_API_VERSION: &str = "v3_0.1";
+
+/// PolishAPI service
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Service {
+    /// Authorization Service
+    #[serde(rename = "as")]
+    Auth,
+    /// Account Information Service
+    #[serde(rename = "ais")]
+    Accounts,
+    /// Payment Initiation Service
+    #[serde(rename = "pis")]
+    Payments,
+    /// Confirmation of the Availability of Funds
+    #[serde(rename = "caf")]
+    Funds,
+}
+
+impl Service {
+    /// All services
+    pub const ALL: [Service; 4] = [Service::Auth, Service::Accounts, Service::Payments, Service::Funds];
+
+    /// Path segment of the service, e.g. `accounts`
+    pub fn path_segment(&self) -> &'static str {
+        match self {
+            Service::Auth => "auth",
+            Service::Accounts => "accounts",
+            Service::Payments => "payments",
+            Service::Funds => "funds",
+        }
+    }
+}
+
+impl fmt::Display for Service {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Service::Auth => "AS",
+            Service::Accounts => "AIS",
+            Service::Payments => "PIS",
+            Service::Funds => "CAF",
+        };
+        f.write_str(name)
+    }
+}
+
+/// PolishAPI endpoint, named after its operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Endpoint {
+    Authorize,
+    AuthorizeExt,
+    Token,
+    Register,
+    GetAccounts,
+    GetAccount,
+    GetTransactionsDone,
+    GetTransactionsPending,
+    GetTransactionsRejected,
+    GetTransactionsCancelled,
+    GetTransactionsScheduled,
+    GetTransactionDetail,
+    GetHolds,
+    DeleteConsent,
+    Domestic,
+    #[serde(rename = "EEA")]
+    Eea,
+    #[serde(rename = "nonEEA")]
+    NonEea,
+    Tax,
+    GetPayment,
+    Confirmation,
+}
+
+impl Endpoint {
+    /// Service the endpoint belongs to
+    pub fn service(&self) -> Service {
+        match self {
+            Endpoint::Authorize | Endpoint::AuthorizeExt | Endpoint::Token | Endpoint::Register => Service::Auth,
+            Endpoint::GetAccounts
+            | Endpoint::GetAccount
+            | Endpoint::GetTransactionsDone
+            | Endpoint::GetTransactionsPending
+            | Endpoint::GetTransactionsRejected
+            | Endpoint::GetTransactionsCancelled
+            | Endpoint::GetTransactionsScheduled
+            | Endpoint::GetTransactionDetail
+            | Endpoint::GetHolds
+            | Endpoint::DeleteConsent => Service::Accounts,
+            Endpoint::Domestic | Endpoint::Eea | Endpoint::NonEea | Endpoint::Tax | Endpoint::GetPayment => {
+                Service::Payments
+            }
+            Endpoint::Confirmation => Service::Funds,
+        }
+    }
+
+    /// Operation name, the last segment of the path
+    pub fn operation(&self) -> &'static str {
+        match self {
+            Endpoint::Authorize => "authorize",
+            Endpoint::AuthorizeExt => "authorizeExt",
+            Endpoint::Token => "token",
+            Endpoint::Register => "register",
+            Endpoint::GetAccounts => "getAccounts",
+            Endpoint::GetAccount => "getAccount",
+            Endpoint::GetTransactionsDone => "getTransactionsDone",
+            Endpoint::GetTransactionsPending => "getTransactionsPending",
+            Endpoint::GetTransactionsRejected => "getTransactionsRejected",
+            Endpoint::GetTransactionsCancelled => "getTransactionsCancelled",
+            Endpoint::GetTransactionsScheduled => "getTransactionsScheduled",
+            Endpoint::GetTransactionDetail => "getTransactionDetail",
+            Endpoint::GetHolds => "getHolds",
+            Endpoint::DeleteConsent => "deleteConsent",
+            Endpoint::Domestic => "domestic",
+            Endpoint::Eea => "EEA",
+            Endpoint::NonEea => "nonEEA",
+            Endpoint::Tax => "tax",
+            Endpoint::GetPayment => "getPayment",
+            Endpoint::Confirmation => "confirmation",
+        }
+    }
+
+    /// Path of the endpoint for the given API version, e.g. `/v3_0.1/accounts/v3_0.1/getAccounts`
+    pub fn path(&self, api_version: &str) -> String {
+        format!("/{0}/{1}/{0}/{2}", api_version, self.service().path_segment(), self.operation())
+    }
+
+    /// Path of the endpoint for [`DEFAULT_API_VERSION`]
+    pub fn default_path(&self) -> String {
+        self.path(DEFAULT_API_VERSION)
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.operation())
+    }
+}
+
+/// Description of one bank's PolishAPI deployment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AspspProfile {
+    /// Identifier used to look the profile up, e.g. `pko`
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    pub base_url: Url,
+    /// Prefix put before every endpoint path, e.g. `/openbanking`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path_prefix: Option<String>,
+    #[serde(default = "default_api_version")]
+    pub api_version: String,
+    /// Services the bank offers
+    #[serde(default = "default_services")]
+    pub services: BTreeSet<Service>,
+    /// Paths replacing the standard ones, relative to `base_url`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub endpoints: HashMap<Endpoint, String>,
+    #[serde(default)]
+    pub client_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+    #[serde(default)]
+    pub client_auth_method: ClientAuthMethod,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tpp_id: Option<String>,
+    /// PEM file with the QWAC certificate chain used for mutual TLS
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_certificate: Option<PathBuf>,
+    /// PEM file with the private key of the QWAC certificate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_key: Option<PathBuf>,
+    /// PEM files with additional root CAs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub root_certificates: Vec<PathBuf>,
+}
+
+fn default_api_version() -> String {
+    DEFAULT_API_VERSION.to_string()
+}
+
+fn default_services() -> BTreeSet<Service> {
+    Service::ALL.into_iter().collect()
+}
+
+impl AspspProfile {
+    /// Create a profile offering every service with the standard paths
+    pub fn new(id: impl Into<String>, base_url: &str) -> Result<Self> {
+        let base_url = Url::parse(base_url)
+            .map_err(|e| PolishApiError::Config(format!("Invalid base URL: {}", e)))?;
+
+        Ok(Self {
+            id: id.into(),
+            name: String::new(),
+            base_url,
+            path_prefix: None,
+            api_version: default_api_version(),
+            services: default_services(),
+            endpoints: HashMap::new(),
+            client_id: String::new(),
+            client_secret: None,
+            client_auth_method: ClientAuthMethod::default(),
+            tpp_id: None,
+            client_certificate: None,
+            client_key: None,
+            root_certificates: Vec::new(),
+        })
+    }
+
+    /// Whether the bank offers the service
+    pub fn supports(&self, service: Service) -> bool {
+        self.services.contains(&service)
+    }
+
+    /// Path of an endpoint at this bank
+    ///
+    /// An entry in `endpoints` is used as is; otherwise the standard path for
+    /// `api_version` is put after `path_prefix`.
+    pub fn path(&self, endpoint: Endpoint) -> String {
+        if let Some(path) = self.endpoints.get(&endpoint) {
+            return path.clone();
+        }
+        let path = endpoint.path(&self.api_version);
+        match &self.path_prefix {
+            Some(prefix) => format!("/{}{}", prefix.trim_matches('/'), path),
+            None => path,
+        }
+    }
+
+    /// Build a client configuration for this bank
+    ///
+    /// Certificate files are read here.
+    pub fn config(&self) -> Result<Config> {
+        let mut config = Config::new(self.base_url.as_str())?
+            .with_client_id(self.client_id.clone())
+            .with_client_auth_method(self.client_auth_method)
+            .with_profile(self.clone());
+        config.client_secret = self.client_secret.clone();
+        config.tpp_id = self.tpp_id.clone();
+
+        match (&self.client_certificate, &self.client_key) {
+            (Some(certificate), Some(key)) => {
+                config = config.with_client_identity(ClientIdentity::from_pem(std::fs::read(certificate)?, std::fs::read(key)?));
+            }
+            (None, None) => {}
+            _ => {
+                return Err(PolishApiError::Config(format!(
+                    "ASPSP profile {} needs both client_certificate and client_key",
+                    self.id
+                )))
+            }
+        }
+        for path in &self.root_certificates {
+            config = config.with_root_certificate_pem(std::fs::read(path)?);
+        }
+        Ok(config)
+    }
+}
+
+/// Set of ASPSP profiles keyed by their ID
+#[derive(Debug, Clone, Default)]
+pub struct ProfileRegistry {
+    profiles: HashMap<String, AspspProfile>,
+}
+
+/// Layout of a profile file: a list of profiles under `profiles`
+#[derive(Deserialize)]
+struct ProfileFile {
+    profiles: Vec<AspspProfile>,
+}
+
+impl ProfileRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse profiles from JSON (`{"profiles": [...]}`)
+    pub fn from_json(json: &str) -> Result<Self> {
+        let file: ProfileFile = serde_json::from_str(json)?;
+        Self::from_profiles(file.profiles)
+    }
+
+    /// Parse profiles from TOML (`[[profiles]]` tables)
+    pub fn from_toml(toml: &str) -> Result<Self> {
+        let file: ProfileFile = toml::from_str(toml)
+            .map_err(|e| PolishApiError::Config(format!("Invalid ASPSP profiles: {}", e)))?;
+        Self::from_profiles(file.profiles)
+    }
+
+    /// Load profiles from a `.toml` or `.json` file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Self::from_toml(&contents),
+            Some("json") => Self::from_json(&contents),
+            _ => Err(PolishApiError::Config(format!(
+                "Unsupported ASPSP profile file {}; expected .toml or .json",
+                path.display()
+            ))),
+        }
+    }
+
+    fn from_profiles(profiles: Vec<AspspProfile>) -> Result<Self> {
+        let mut registry = Self::new();
+        for profile in profiles {
+            if registry.profiles.contains_key(&profile.id) {
+                return Err(PolishApiError::Config(format!("Duplicate ASPSP profile {}", profile.id)));
+            }
+            registry.insert(profile);
+        }
+        Ok(registry)
+    }
+
+    /// Add a profile, replacing one with the same ID
+    pub fn insert(&mut self, profile: AspspProfile) {
+        self.profiles.insert(profile.id.clone(), profile);
+    }
+
+    /// Get a profile by ID
+    pub fn get(&self, id: &str) -> Option<&AspspProfile> {
+        self.profiles.get(id)
+    }
+
+    /// All profiles
+    pub fn profiles(&self) -> impl Iterator<Item = &AspspProfile> {
+        self.profiles.values()
+    }
+
+    /// Profiles of the banks offering the service
+    pub fn supporting(&self, service: Service) -> impl Iterator<Item = &AspspProfile> {
+        self.profiles.values().filter(move |profile| profile.supports(service))
+    }
+
+    /// Build the client configuration for a bank
+    pub fn config(&self, id: &str) -> Result<Config> {
+        self.get(id)
+            .ok_or_else(|| PolishApiError::Config(format!("Unknown ASPSP profile {}", id)))?
+            .config()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROFILES_TOML: &str = r#"
+        [[profiles]]
+        id = "standard"
+        name = "Standard Bank"
+        base_url = "https://api.standard.example.com"
+        client_id = "tpp-standard"
+
+        [[profiles]]
+        id = "quirky"
+        base_url = "https://quirky.example.com"
+        path_prefix = "/openbanking/"
+        api_version = "v2_1.1"
+        services = ["as", "ais"]
+        client_auth_method = "private_key_jwt"
+
+        [profiles.endpoints]
+        getHolds = "/custom/holds"
+    "#;
+
+    #[test]
+    fn test_endpoint_paths() {
+        assert_eq!(Endpoint::GetAccounts.default_path(), "/v3_0.1/accounts/v3_0.1/getAccounts");
+        assert_eq!(Endpoint::NonEea.default_path(), "/v3_0.1/payments/v3_0.1/nonEEA");
+        assert_eq!(Endpoint::Token.path("v2_1.1"), "/v2_1.1/auth/v2_1.1/token");
+        assert_eq!(serde_json::to_value(Endpoint::Eea).unwrap(), "EEA");
+        assert_eq!(serde_json::to_value(Endpoint::GetTransactionsDone).unwrap(), "getTransactionsDone");
+    }
+
+    #[test]
+    fn test_load_toml() {
+        let registry = ProfileRegistry::from_toml(PROFILES_TOML).unwrap();
+
+        let standard = registry.get("standard").unwrap();
+        assert!(Service::ALL.iter().all(|service| standard.supports(*service)));
+        assert_eq!(standard.path(Endpoint::Confirmation), "/v3_0.1/funds/v3_0.1/confirmation");
+
+        let quirky = registry.get("quirky").unwrap();
+        assert!(!quirky.supports(Service::Payments));
+        assert_eq!(quirky.path(Endpoint::GetAccounts), "/openbanking/v2_1.1/accounts/v2_1.1/getAccounts");
+        assert_eq!(quirky.path(Endpoint::GetHolds), "/custom/holds");
+        assert_eq!(registry.supporting(Service::Payments).map(|profile| profile.id.as_str()).collect::<Vec<_>>(), ["standard"]);
+
+        let config = registry.config("quirky").unwrap();
+        assert_eq!(config.client_auth_method, ClientAuthMethod::PrivateKeyJwt);
+        assert!(matches!(registry.config("missing"), Err(PolishApiError::Config(_))));
+    }
+
+    #[test]
+    fn test_load_json_rejects_duplicates() {
+        let json = r#"{"profiles": [
+            {"id": "bank", "base_url": "https://bank.example.com"},
+            {"id": "bank", "base_url": "https://other.example.com"}
+        ]}"#;
+        assert!(matches!(ProfileRegistry::from_json(json), Err(PolishApiError::Config(_))));
+
+        let registry = ProfileRegistry::from_json(&json.replacen(r#""bank""#, r#""first""#, 1)).unwrap();
+        assert_eq!(registry.profiles().count(), 2);
+    }
+}
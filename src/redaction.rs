@@ -0,0 +1,274 @@
+//! Masking of PSU data and secrets in logs
+//!
+//! Bodies logged by the client with [`Config::with_body_logging`] pass through
+//! a [`RedactionPolicy`] first, so logs can be shipped without leaking account
+//! numbers, card numbers, tokens, client secrets or personal names.
+//!
+//! [`Config::with_body_logging`]: crate::Config::with_body_logging
+
+use std::collections::HashSet;
+
+use serde_json::Value;
+
+/// Replacement for values that are masked completely
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Fields whose values are always masked completely
+const SENSITIVE_FIELDS: &[&str] = &[
+    // Credentials and tokens
+    "token",
+    "access_token",
+    "refresh_token",
+    "exchange_token",
+    "client_secret",
+    "client_assertion",
+    "code_verifier",
+    "eatCode",
+    "software_statement",
+    "apiKey",
+    "password",
+    // Personal data
+    "name",
+    "nameAddress",
+    "psuIdentifierValue",
+    "psuContextIdentifierValue",
+    "ipAddress",
+    "email",
+    "phone",
+];
+
+/// Field holding the authorization code in token requests
+///
+/// Elsewhere `code` names dictionary entries (account types, statuses) and is kept.
+const AUTHORIZATION_CODE_FIELD: &str = "code";
+
+/// Rules for masking PSU data and secrets in logged bodies
+///
+/// Values of sensitive fields (tokens, client secrets, authorization codes in
+/// token requests, names and addresses, PSU identifiers) are replaced with [`REDACTED`].
+/// Anywhere else, strings that look like an IBAN, a Polish NRB account number
+/// or a card number (PAN) keep only their last four characters.
+#[derive(Debug, Clone)]
+pub struct RedactionPolicy {
+    fields: HashSet<String>,
+}
+
+impl Default for RedactionPolicy {
+    fn default() -> Self {
+        Self {
+            fields: SENSITIVE_FIELDS.iter().map(|field| field.to_ascii_lowercase()).collect(),
+        }
+    }
+}
+
+impl RedactionPolicy {
+    /// Create the default policy
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also mask the values of the given field (case-insensitive)
+    pub fn with_field(mut self, field: impl AsRef<str>) -> Self {
+        self.fields.insert(field.as_ref().to_ascii_lowercase());
+        self
+    }
+
+    /// Copy of a JSON value with sensitive data masked
+    pub fn redact_json(&self, value: &Value) -> Value {
+        match value {
+            Value::Object(object) => Value::Object(
+                object
+                    .iter()
+                    .map(|(key, value)| {
+                        let sensitive = self.fields.contains(&key.to_ascii_lowercase())
+                            || (key == AUTHORIZATION_CODE_FIELD && object.contains_key("grant_type"));
+                        let value = if sensitive && !value.is_null() {
+                            Value::from(REDACTED)
+                        } else {
+                            self.redact_json(value)
+                        };
+                        (key.clone(), value)
+                    })
+                    .collect(),
+            ),
+            Value::Array(items) => Value::Array(items.iter().map(|item| self.redact_json(item)).collect()),
+            Value::String(text) => Value::String(mask_identifier(text).unwrap_or_else(|| self.redact_text(text))),
+            other => other.clone(),
+        }
+    }
+
+    /// Body with sensitive data masked, parsed as JSON where possible
+    pub fn redact_body(&self, body: &[u8]) -> String {
+        match serde_json::from_slice::<Value>(body) {
+            Ok(value) => self.redact_json(&value).to_string(),
+            Err(_) => self.redact_text(&String::from_utf8_lossy(body)),
+        }
+    }
+
+    /// Free text with account and card numbers masked
+    ///
+    /// Numbers written in groups separated by single spaces or hyphens, like
+    /// `27 1140 2004 0000 3002 0135 5387`, are masked as a whole.
+    pub fn redact_text(&self, text: &str) -> String {
+        let mut redacted = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find(|character: char| character.is_ascii_alphanumeric()) {
+            redacted.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let ends = group_ends(rest);
+            let (end, word) = ends
+                .iter()
+                .rev()
+                .find_map(|&end| mask_identifier(&rest[..end]).map(|masked| (end, masked)))
+                .unwrap_or_else(|| (ends[0], rest[..ends[0]].to_string()));
+            redacted.push_str(&word);
+            rest = &rest[end..];
+        }
+        redacted.push_str(rest);
+        redacted
+    }
+}
+
+/// Longest identifier [`mask_identifier`] recognizes (an IBAN)
+const MAX_IDENTIFIER_LEN: usize = 34;
+
+/// Ends of the runs of groups at the start of `text`, shortest first
+///
+/// The first group is any alphanumeric word (e.g. `PL61`), the following ones
+/// are digits joined by a single space or hyphen, up to [`MAX_IDENTIFIER_LEN`]
+/// characters without separators.
+fn group_ends(text: &str) -> Vec<usize> {
+    let bytes = text.as_bytes();
+    let mut ends = Vec::new();
+    let mut position = 0;
+    let mut length = 0;
+    loop {
+        let group = bytes[position..].iter().take_while(|byte| byte.is_ascii_alphanumeric()).count();
+        let digits = bytes[position..position + group].iter().all(u8::is_ascii_digit);
+        if !ends.is_empty() && (!digits || length + group > MAX_IDENTIFIER_LEN) {
+            break;
+        }
+        position += group;
+        length += group;
+        ends.push(position);
+        match bytes.get(position..position + 2) {
+            Some([b' ' | b'-', next]) if next.is_ascii_alphanumeric() => position += 1,
+            _ => break,
+        }
+    }
+    ends
+}
+
+/// Mask an IBAN, NRB or PAN, keeping its last four characters
+fn mask_identifier(text: &str) -> Option<String> {
+    let compact: String = text.chars().filter(|character| !matches!(character, ' ' | '-')).collect();
+    if !(is_iban(&compact) || is_nrb(&compact) || is_pan(&compact)) {
+        return None;
+    }
+    Some(format!("****{}", &compact[compact.len() - 4..]))
+}
+
+fn is_iban(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    (15..=34).contains(&bytes.len())
+        && bytes[..2].iter().all(u8::is_ascii_uppercase)
+        && bytes[2..4].iter().all(u8::is_ascii_digit)
+        && bytes[4..].iter().all(u8::is_ascii_alphanumeric)
+}
+
+fn is_nrb(text: &str) -> bool {
+    text.len() == 26 && text.bytes().all(|byte| byte.is_ascii_digit())
+}
+
+/// 13 to 19 digits passing the Luhn check
+fn is_pan(text: &str) -> bool {
+    if !(13..=19).contains(&text.len()) || !text.bytes().all(|byte| byte.is_ascii_digit()) {
+        return false;
+    }
+    let sum: u32 = text
+        .bytes()
+        .rev()
+        .enumerate()
+        .map(|(position, byte)| {
+            let digit = u32::from(byte - b'0');
+            match position % 2 {
+                1 if digit * 2 > 9 => digit * 2 - 9,
+                1 => digit * 2,
+                _ => digit,
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_redact_json() {
+        let body = json!({
+            "requestHeader": { "token": "secret-token", "requestId": "2c1b0a9f-8e7d-4c6b-a5f4-e3d2c1b0a9f8" },
+            "accountNumber": "PL61109010140000071219812874",
+            "recipient": {
+                "accountNumber": "27 1140 2004 0000 3002 0135 5387",
+                "nameAddress": { "value": ["Jan Kowalski", "ul. Prosta 1"] }
+            },
+            "accountType": { "code": "CACC" },
+            "card": "4111 1111 1111 1111",
+            "client_secret": "s3cret",
+            "amount": "1250.00",
+            "description": "Invoice 2024/05"
+        });
+
+        let redacted = RedactionPolicy::new().with_field("description").redact_json(&body);
+        assert_eq!(
+            redacted,
+            json!({
+                "requestHeader": { "token": REDACTED, "requestId": "2c1b0a9f-8e7d-4c6b-a5f4-e3d2c1b0a9f8" },
+                "accountNumber": "****2874",
+                "recipient": { "accountNumber": "****5387", "nameAddress": REDACTED },
+                "accountType": { "code": "CACC" },
+                "card": "****1111",
+                "client_secret": REDACTED,
+                "amount": "1250.00",
+                "description": REDACTED
+            })
+        );
+    }
+
+    #[test]
+    fn test_redact_text() {
+        let policy = RedactionPolicy::new();
+        assert_eq!(
+            policy.redact_body(b"Account PL61109010140000071219812874 is blocked (card 4111111111111111)"),
+            "Account ****2874 is blocked (card ****1111)"
+        );
+        assert_eq!(
+            policy.redact_body(br#"{"grant_type":"authorization_code","code":"abc123"}"#),
+            r#"{"code":"[REDACTED]","grant_type":"authorization_code"}"#
+        );
+        // Numbers failing the Luhn check are not card numbers
+        assert_eq!(policy.redact_text("order 4111111111111112"), "order 4111111111111112");
+    }
+
+    #[test]
+    fn test_redact_grouped_numbers() {
+        let policy = RedactionPolicy::new();
+        assert_eq!(
+            policy.redact_text("Transfer to 27 1140 2004 0000 3002 0135 5387 failed"),
+            "Transfer to ****5387 failed"
+        );
+        assert_eq!(
+            policy.redact_text("IBAN PL61 1090 1014 0000 0712 1981 2874, card 4111-1111-1111-1111."),
+            "IBAN ****2874, card ****1111."
+        );
+        // Groups after the account number are kept
+        assert_eq!(
+            policy.redact_text("27 1140 2004 0000 3002 0135 5387 2024 05"),
+            "****5387 2024 05"
+        );
+        assert_eq!(policy.redact_text("amount 1 250 00 PLN on 2024-05-01"), "amount 1 250 00 PLN on 2024-05-01");
+    }
+}
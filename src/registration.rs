@@ -0,0 +1,135 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::crypto::{sign_compact, Signer};
+use crate::types::{ClientMetadata, PolishApiError, Result};
+
+/// Default validity of a software statement
+pub const SOFTWARE_STATEMENT_LIFETIME: Duration = Duration::from_secs(600);
+
+/// Validity of a `private_key_jwt` client assertion
+pub const CLIENT_ASSERTION_LIFETIME: Duration = Duration::from_secs(60);
+
+/// Sign a software statement (RFC 7591 section 2.3) asserting the client metadata
+///
+/// The statement is a JWT signed with the TPP's QSealC key. Its claims are
+/// the metadata plus `iss`, `iat`, `exp` and a unique `jti`; the certificate
+/// headers of the signer (`x5c`, `x5t#S256`) let the ASPSP identify the TPP.
+pub async fn sign_software_statement(
+    signer: &dyn Signer,
+    issuer: &str,
+    metadata: &ClientMetadata,
+    lifetime: Duration,
+) -> Result<String> {
+    let mut claims = match serde_json::to_value(metadata)? {
+        Value::Object(claims) => claims,
+        _ => return Err(PolishApiError::Internal("Client metadata is not a JSON object".to_string())),
+    };
+
+    let issued_at = Utc::now().timestamp();
+    let lifetime = i64::try_from(lifetime.as_secs()).unwrap_or(i64::MAX);
+    claims.insert("iss".to_string(), json!(issuer));
+    claims.insert("iat".to_string(), json!(issued_at));
+    claims.insert("exp".to_string(), json!(issued_at.saturating_add(lifetime)));
+    claims.insert("jti".to_string(), json!(Uuid::new_v4().to_string()));
+
+    sign_compact(signer, "JWT", &serde_json::to_vec(&claims)?).await
+}
+
+/// Sign a `private_key_jwt` client assertion (RFC 7523) for the token endpoint
+///
+/// `iss` and `sub` are the client ID and `aud` is the token endpoint URL.
+pub async fn sign_client_assertion(signer: &dyn Signer, client_id: &str, audience: &str) -> Result<String> {
+    let issued_at = Utc::now().timestamp();
+    let claims = json!({
+        "iss": client_id,
+        "sub": client_id,
+        "aud": audience,
+        "jti": Uuid::new_v4().to_string(),
+        "iat": issued_at,
+        "exp": issued_at + CLIENT_ASSERTION_LIFETIME.as_secs() as i64,
+    });
+
+    sign_compact(signer, "JWT", &serde_json::to_vec(&claims)?).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{CertificateHeaders, JwsSigner};
+    use crate::types::Jwks;
+    use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+
+    const RSA_PRIVATE_KEY: &str = include_str!("../testdata/rsa_private_key.pem");
+    const RSA_PUBLIC_KEY: &str = include_str!("../testdata/rsa_public_key.pem");
+    const RSA_CERTIFICATE: &str = include_str!("../testdata/rsa_certificate.pem");
+
+    fn metadata(signer: &JwsSigner) -> ClientMetadata {
+        ClientMetadata {
+            client_name: "Example TPP".to_string(),
+            redirect_uris: vec!["https://tpp.example.com/redirect".to_string()],
+            grant_types: vec!["authorization_code".to_string(), "refresh_token".to_string()],
+            response_types: vec!["code".to_string()],
+            scope: "ais pis".to_string(),
+            token_endpoint_auth_method: "private_key_jwt".to_string(),
+            contacts: Some(vec!["ops@tpp.example.com".to_string()]),
+            jwks: Some(Jwks { keys: vec![signer.public_jwk()] }),
+            software_id: Some("tpp-app".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_software_statement_verifies() {
+        let signer = JwsSigner::from_pem(RSA_PRIVATE_KEY, "qseal".to_string())
+            .unwrap()
+            .with_certificate_headers(CertificateHeaders::from_pem_chain(RSA_CERTIFICATE).unwrap());
+        let metadata = metadata(&signer);
+
+        let statement = sign_software_statement(&signer, "PSDPL-KNF-0000000000", &metadata, SOFTWARE_STATEMENT_LIFETIME)
+            .await
+            .unwrap();
+
+        let header = decode_header(&statement).unwrap();
+        assert_eq!(header.alg, Algorithm::RS256);
+        assert_eq!(header.typ.as_deref(), Some("JWT"));
+        assert_eq!(header.kid.as_deref(), Some("qseal"));
+        assert!(header.x5c.is_some());
+
+        let mut validation = Validation::new(Algorithm::RS256);
+        validation.set_issuer(&["PSDPL-KNF-0000000000"]);
+        let claims = decode::<Value>(&statement, &DecodingKey::from_rsa_pem(RSA_PUBLIC_KEY.as_bytes()).unwrap(), &validation)
+            .unwrap()
+            .claims;
+        assert_eq!(claims["client_name"], "Example TPP");
+        assert_eq!(claims["token_endpoint_auth_method"], "private_key_jwt");
+        assert_eq!(claims["jwks"]["keys"][0]["kty"], "RSA");
+        assert_eq!(claims["exp"].as_i64().unwrap() - claims["iat"].as_i64().unwrap(), 600);
+    }
+
+    #[test]
+    fn test_public_jwk_matches_key() {
+        let signer = JwsSigner::from_pem(RSA_PRIVATE_KEY, "qseal".to_string()).unwrap();
+        let jwk = signer.public_jwk();
+        let key = DecodingKey::from_rsa_components(jwk.n.as_deref().unwrap(), jwk.e.as_deref().unwrap()).unwrap();
+
+        let token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::new(Algorithm::RS256),
+            &json!({ "sub": "tpp" }),
+            &jsonwebtoken::EncodingKey::from_rsa_pem(RSA_PRIVATE_KEY.as_bytes()).unwrap(),
+        )
+        .unwrap();
+        let mut validation = Validation::new(Algorithm::RS256);
+        validation.required_spec_claims.clear();
+        validation.validate_exp = false;
+        assert!(decode::<Value>(&token, &key, &validation).is_ok());
+
+        let ec = JwsSigner::from_pem(include_str!("../testdata/ec_private_key.pem"), "ec".to_string()).unwrap();
+        let jwk = ec.public_jwk();
+        assert_eq!((jwk.kty.as_str(), jwk.crv.as_deref()), ("EC", Some("P-256")));
+        assert_eq!(jwk.x.unwrap().len(), 43);
+    }
+}
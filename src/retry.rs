@@ -0,0 +1,274 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use ring::rand::{SecureRandom, SystemRandom};
+
+use crate::profile::Endpoint;
+
+/// When and how often failed calls are retried
+///
+/// Connection errors, timeouts and `429`, `502`, `503` and `504` responses are
+/// retried with jittered exponential back-off; a `Retry-After` header from the
+/// bank takes precedence over the computed delay. Only reads are retried by
+/// default. Payment initiations are re-sent only when allowed with
+/// [`RetryPolicy::with_payment_retries`], and always with the same signed body,
+/// so the bank sees the same `requestId` and can detect the duplicate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    retry_payments: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+            retry_payments: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Create a policy making up to 3 attempts, starting with a 500 ms delay
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a policy that never retries
+    pub fn disabled() -> Self {
+        Self::default().with_max_attempts(1)
+    }
+
+    /// Set the number of attempts, including the first one
+    pub fn with_max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// Set the delay before the first retry
+    pub fn with_initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Set the longest delay between attempts
+    ///
+    /// A `Retry-After` asking for a longer wait ends the retries.
+    pub fn with_max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Set the factor the delay grows by after each attempt
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Allow payment initiations to be re-sent with the same `requestId`
+    pub fn with_payment_retries(mut self, allowed: bool) -> Self {
+        self.retry_payments = allowed;
+        self
+    }
+
+    /// Number of attempts, including the first one
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Whether calls to the endpoint may be retried
+    pub fn allows(&self, endpoint: Endpoint) -> bool {
+        match endpoint {
+            Endpoint::GetAccounts
+            | Endpoint::GetAccount
+            | Endpoint::GetTransactionsDone
+            | Endpoint::GetTransactionsPending
+            | Endpoint::GetTransactionsRejected
+            | Endpoint::GetTransactionsCancelled
+            | Endpoint::GetTransactionsScheduled
+            | Endpoint::GetTransactionDetail
+            | Endpoint::GetHolds
+            | Endpoint::GetPayment
+            | Endpoint::Confirmation => true,
+            Endpoint::Domestic | Endpoint::Eea | Endpoint::NonEea | Endpoint::Tax => self.retry_payments,
+            // Authorization codes are single use and consents are deleted once
+            Endpoint::Authorize
+            | Endpoint::AuthorizeExt
+            | Endpoint::Token
+            | Endpoint::Register
+            | Endpoint::DeleteConsent => false,
+        }
+    }
+
+    /// Delay before the given retry (1 for the first), or `None` to stop retrying
+    ///
+    /// The computed back-off is jittered to between half and all of its value.
+    pub fn delay(&self, retry: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        if retry >= self.max_attempts {
+            return None;
+        }
+        if let Some(retry_after) = retry_after {
+            return (retry_after <= self.max_backoff).then_some(retry_after);
+        }
+
+        let exponent = i32::try_from(retry - 1).unwrap_or(i32::MAX);
+        let backoff = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+        let backoff = backoff.min(self.max_backoff.as_secs_f64());
+        Some(Duration::from_secs_f64(backoff * (0.5 + jitter() / 2.0)))
+    }
+}
+
+/// Whether a response status is worth retrying
+pub(crate) fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Whether a transport error is worth retrying
+pub(crate) fn is_retryable_error(error: &reqwest::Error) -> bool {
+    error.is_connect() || error.is_timeout() || error.is_request()
+}
+
+/// Parse a `Retry-After` header given in seconds or as an HTTP date
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some((date.with_timezone(&Utc) - Utc::now()).to_std().unwrap_or(Duration::ZERO))
+}
+
+/// Random number in `[0, 1)`
+fn jitter() -> f64 {
+    let mut bytes = [0u8; 4];
+    match SystemRandom::new().fill(&mut bytes) {
+        Ok(()) => f64::from(u32::from_be_bytes(bytes)) / (f64::from(u32::MAX) + 1.0),
+        Err(_) => 0.5,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, response_body};
+    use crate::types::{DomesticPaymentRequest, GetAccountsRequest, PolishApiError, RequestHeaders};
+    use crate::PolishApiClient;
+    const GET_ACCOUNTS: &str = "/v3_0.1/accounts/v3_0.1/getAccounts";
+    const DOMESTIC: &str = "/v3_0.1/payments/v3_0.1/domestic";
+
+    fn fast_policy() -> RetryPolicy {
+        RetryPolicy::new().with_initial_backoff(Duration::from_millis(5))
+    }
+
+    async fn client(server: &mockito::Server, policy: RetryPolicy) -> PolishApiClient {
+        test_support::client(test_support::config(server).with_retry_policy(policy)).await
+    }
+
+    fn accounts_request() -> GetAccountsRequest {
+        GetAccountsRequest { request_header: Default::default(), page_id: None, per_page: None }
+    }
+
+    fn payment_request() -> DomesticPaymentRequest {
+        serde_json::from_str(include_str!("../testdata/fixtures/pis/domestic_request.json")).unwrap()
+    }
+
+    #[test]
+    fn test_delay() {
+        let policy = fast_policy().with_max_backoff(Duration::from_millis(12));
+        let first = policy.delay(1, None).unwrap();
+        assert!(first >= Duration::from_micros(2500) && first <= Duration::from_millis(5));
+        assert!(policy.delay(2, None).unwrap() <= Duration::from_millis(10));
+        assert_eq!(policy.delay(3, None), None);
+
+        assert_eq!(policy.delay(1, Some(Duration::from_millis(7))), Some(Duration::from_millis(7)));
+        assert_eq!(policy.delay(1, Some(Duration::from_secs(60))), None);
+        assert!(!policy.allows(Endpoint::Domestic));
+        assert!(policy.with_payment_retries(true).allows(Endpoint::Domestic));
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, "120".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(120)));
+
+        headers.insert(RETRY_AFTER, "Sun, 06 Nov 1994 08:49:37 GMT".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+
+        headers.insert(RETRY_AFTER, "soon".parse().unwrap());
+        assert_eq!(retry_after(&headers), None);
+    }
+
+    #[tokio::test]
+    async fn test_read_is_retried() {
+        let mut server = mockito::Server::new_async().await;
+        let unavailable = server
+            .mock("POST", GET_ACCOUNTS)
+            .with_status(503)
+            .with_header("Retry-After", "0")
+            .expect(2)
+            .create_async()
+            .await;
+        let ok = server
+            .mock("POST", GET_ACCOUNTS)
+            .with_body(response_body(r#""accounts":[]"#))
+            .create_async()
+            .await;
+        let client = client(&server, fast_policy()).await;
+
+        assert!(client.accounts().get_accounts(accounts_request(), RequestHeaders::default()).await.is_ok());
+        unavailable.assert_async().await;
+        ok.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_read_gives_up_after_max_attempts() {
+        let mut server = mockito::Server::new_async().await;
+        let unavailable = server.mock("POST", GET_ACCOUNTS).with_status(503).expect(3).create_async().await;
+        let client = client(&server, fast_policy()).await;
+
+        let result = client.accounts().get_accounts(accounts_request(), RequestHeaders::default()).await;
+        assert!(matches!(result, Err(PolishApiError::Api { .. })));
+        unavailable.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_payment_is_only_retried_when_allowed() {
+        let mut server = mockito::Server::new_async().await;
+
+        for (policy, attempts) in [(fast_policy(), 1), (fast_policy().with_payment_retries(true), 3)] {
+            // Every attempt carries the requestId of the fixture
+            let request_id = "2c1b0a9f-8e7d-4c6b-a5f4-e3d2c1b0a9f8";
+            let unavailable = server
+                .mock("POST", DOMESTIC)
+                .match_header("X-REQUEST-ID", request_id)
+                .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                    "requestHeader": { "requestId": request_id }
+                })))
+                .with_status(503)
+                .expect(attempts)
+                .create_async()
+                .await;
+
+            let client = client(&server, policy).await;
+            let result = client.payments().initiate_domestic_payment(payment_request(), RequestHeaders::default()).await;
+            assert!(matches!(result, Err(PolishApiError::Api { .. })));
+            unavailable.assert_async().await;
+            unavailable.remove_async().await;
+        }
+    }
+}
@@ -0,0 +1,34 @@
+//! Fixtures shared by the unit tests
+
+use std::sync::Arc;
+
+use crate::crypto::JwsSigner;
+use crate::{Config, PolishApiClient};
+
+/// Signer with the test RSA key
+pub(crate) fn signer() -> JwsSigner {
+    JwsSigner::from_pem(include_str!("../testdata/rsa_private_key.pem"), "key".to_string()).unwrap()
+}
+
+/// Configuration for a mock bank, with client ID `client`
+pub(crate) fn config(server: &mockito::Server) -> Config {
+    Config::new(&server.url()).unwrap().with_client_id("client")
+}
+
+/// Client signing its requests with [`signer`]
+pub(crate) async fn client(config: Config) -> PolishApiClient {
+    PolishApiClient::new(config).await.unwrap().with_jws_signer(Arc::new(signer()))
+}
+
+/// Response body with a fresh `responseHeader` followed by `fields`, e.g. `"accounts":[]`
+pub(crate) fn response_body(fields: &str) -> String {
+    format!(r#"{{"responseHeader":{{"requestId":"{}"}},{}}}"#, uuid::Uuid::new_v4(), fields)
+}
+
+/// Token response body granting `access_token` for an hour
+pub(crate) fn token_body(access_token: &str) -> String {
+    response_body(&format!(
+        r#""access_token":"{}","token_type":"Bearer","expires_in":3600,"scope":"ais""#,
+        access_token
+    ))
+}
@@ -0,0 +1,610 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::profile::{Endpoint, Service};
+use crate::types::{PolishApiError, Result};
+
+/// Number of calls allowed per period
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    pub limit: u32,
+    pub period: Duration,
+}
+
+impl Quota {
+    /// PSD2 limit for AIS calls made without the PSU: four per 24 hours
+    pub const PSD2_REGULATORY: Quota = Quota::per_day(4);
+
+    /// Create a quota of `limit` calls per `period`
+    pub const fn new(limit: u32, period: Duration) -> Self {
+        Self { limit, period }
+    }
+
+    /// Create a quota of `limit` calls per 24 hours
+    pub const fn per_day(limit: u32) -> Self {
+        Self::new(limit, Duration::from_secs(24 * 60 * 60))
+    }
+
+    /// Start of the rolling period ending at `now`
+    ///
+    /// Periods are rolling rather than calendar-aligned, so a daily quota
+    /// allows `limit` calls in any 24 hours whatever time zone the bank counts in.
+    fn since(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        chrono::Duration::from_std(self.period)
+            .ok()
+            .and_then(|period| now.checked_sub_signed(period))
+            .unwrap_or(DateTime::<Utc>::MIN_UTC)
+    }
+
+    /// Time enough of the counted `calls` leave the period to make room for another call
+    fn resets_at(&self, mut calls: Vec<DateTime<Utc>>) -> DateTime<Utc> {
+        calls.sort_unstable();
+        let freeing = match calls.len().checked_sub(self.limit as usize) {
+            Some(index) => calls.get(index).copied(),
+            // Calls left the period since the quota was checked
+            None => return Utc::now(),
+        };
+        freeing
+            .zip(chrono::Duration::from_std(self.period).ok())
+            .and_then(|(call, period)| call.checked_add_signed(period))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
+    }
+}
+
+/// What happens to a call over its quota
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverQuota {
+    /// Fail with [`PolishApiError::QuotaExceeded`]
+    #[default]
+    Reject,
+    /// Wait for the quota to reset if that happens within `max_wait`, otherwise reject
+    Queue { max_wait: Duration },
+}
+
+/// Times of the calls counted against a quota
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaCounter {
+    pub calls: Vec<DateTime<Utc>>,
+    /// Time the last call leaves the period, after which the counter can be dropped
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A counter a call is checked and counted against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotaSlot<'a> {
+    pub key: &'a str,
+    /// Start of the period; calls counted at or before it no longer count
+    pub since: DateTime<Utc>,
+    /// Calls allowed in the period
+    pub limit: u32,
+}
+
+/// Storage backend for quota counters
+///
+/// Implement this trait to share counters between processes, e.g. in Redis
+/// or a database. `try_acquire` must check and count atomically.
+#[async_trait]
+pub trait QuotaStore: Send + Sync {
+    /// Count a call made at `now` against every slot, unless one of them
+    /// already has `limit` calls in its period; returns whether it was counted
+    ///
+    /// The call is counted against all slots or none. Calls counted at or
+    /// before a slot's `since` may be forgotten.
+    async fn try_acquire(&self, slots: &[QuotaSlot<'_>], now: DateTime<Utc>) -> Result<bool>;
+
+    /// Times of the calls counted after `since`
+    async fn calls(&self, key: &str, since: DateTime<Utc>) -> Result<Vec<DateTime<Utc>>>;
+}
+
+/// Count a call in a map of counters, forgetting calls that left the period
+///
+/// Counters of other keys whose calls all left their period are dropped too.
+fn acquire(counters: &mut HashMap<String, QuotaCounter>, slots: &[QuotaSlot<'_>], now: DateTime<Utc>) -> bool {
+    counters.retain(|_, counter| counter.expires_at > now);
+    for slot in slots {
+        if let Some(counter) = counters.get_mut(slot.key) {
+            counter.calls.retain(|call| *call > slot.since);
+        }
+    }
+    let full = slots.iter().any(|slot| {
+        counters.get(slot.key).map_or(0, |counter| counter.calls.len()) >= slot.limit as usize
+    });
+    if full {
+        return false;
+    }
+    for slot in slots {
+        let expires_at = now.checked_add_signed(now - slot.since).unwrap_or(DateTime::<Utc>::MAX_UTC);
+        let counter = counters
+            .entry(slot.key.to_string())
+            .or_insert_with(|| QuotaCounter { calls: Vec::new(), expires_at });
+        counter.calls.push(now);
+        counter.expires_at = counter.expires_at.max(expires_at);
+    }
+    true
+}
+
+fn calls(counters: &HashMap<String, QuotaCounter>, key: &str, since: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+    counters
+        .get(key)
+        .map(|counter| counter.calls.iter().copied().filter(|call| *call > since).collect())
+        .unwrap_or_default()
+}
+
+/// Quota store that keeps counters in memory
+#[derive(Debug, Default)]
+pub struct InMemoryQuotaStore {
+    counters: Mutex<HashMap<String, QuotaCounter>>,
+}
+
+impl InMemoryQuotaStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl QuotaStore for InMemoryQuotaStore {
+    async fn try_acquire(&self, slots: &[QuotaSlot<'_>], now: DateTime<Utc>) -> Result<bool> {
+        Ok(acquire(&mut *self.counters.lock().await, slots, now))
+    }
+
+    async fn calls(&self, key: &str, since: DateTime<Utc>) -> Result<Vec<DateTime<Utc>>> {
+        Ok(calls(&*self.counters.lock().await, key, since))
+    }
+}
+
+/// Quota store that keeps counters in a JSON file, so they survive restarts
+///
+/// The file is replaced atomically after every counted call and, on Unix, is
+/// readable by the owner only as its keys hold consent IDs and account numbers.
+/// Use one store per file; other processes writing the file are not coordinated with.
+#[derive(Debug)]
+pub struct FileQuotaStore {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl FileQuotaStore {
+    /// Create a store backed by the file at `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), lock: Mutex::new(()) }
+    }
+
+    /// Path of the counter file
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    async fn load(&self) -> Result<HashMap<String, QuotaCounter>> {
+        match tokio::fs::read(&self.path).await {
+            Ok(contents) => Ok(serde_json::from_slice(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn save(&self, counters: &HashMap<String, QuotaCounter>) -> Result<()> {
+        let contents = serde_json::to_vec_pretty(counters)?;
+        let mut temp = self.path.clone().into_os_string();
+        temp.push(".tmp");
+
+        let mut options = tokio::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let mut file = options.open(&temp).await?;
+        tokio::io::AsyncWriteExt::write_all(&mut file, &contents).await?;
+        file.sync_all().await?;
+
+        tokio::fs::rename(&temp, &self.path).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl QuotaStore for FileQuotaStore {
+    async fn try_acquire(&self, slots: &[QuotaSlot<'_>], now: DateTime<Utc>) -> Result<bool> {
+        let _guard = self.lock.lock().await;
+        let mut counters = self.load().await?;
+        if !acquire(&mut counters, slots, now) {
+            return Ok(false);
+        }
+        self.save(&counters).await?;
+        Ok(true)
+    }
+
+    async fn calls(&self, key: &str, since: DateTime<Utc>) -> Result<Vec<DateTime<Utc>>> {
+        let _guard = self.lock.lock().await;
+        Ok(calls(&self.load().await?, key, since))
+    }
+}
+
+/// A call to be counted against the quotas
+#[derive(Debug, Clone, Copy)]
+pub struct ThrottledCall<'a> {
+    pub endpoint: Endpoint,
+    /// Bank the call goes to (profile ID or host)
+    pub aspsp: &'a str,
+    /// Consent the call is made under; calls with an unknown consent share one
+    /// counter per bank and account
+    pub consent_id: Option<&'a str>,
+    pub account_number: Option<&'a str>,
+    /// Whether the PSU takes part in the call (`isDirectPsu`)
+    pub is_direct_psu: bool,
+}
+
+impl ThrottledCall<'_> {
+    /// Whether the call counts against the `psd2Regulatory` limit
+    pub fn is_regulated(&self) -> bool {
+        !self.is_direct_psu && self.endpoint.service() == Service::Accounts && self.endpoint != Endpoint::DeleteConsent
+    }
+
+    /// Counter key of the call for a quota scope
+    fn key(&self, scope: &str) -> String {
+        format!(
+            "{}|{}|{}|{}",
+            self.aspsp,
+            self.consent_id.unwrap_or("*"),
+            self.account_number.unwrap_or("*"),
+            scope
+        )
+    }
+}
+
+/// Client-side rate limiter keyed by bank, consent and account
+///
+/// Enforces the quotas set per endpoint and, unless turned off, the
+/// `psd2Regulatory` limit of four AIS calls per 24 hours and account for calls
+/// made without the PSU (`isDirectPsu` not `true`). Quotas count calls in a
+/// rolling period. Over-quota calls are rejected or queued according to
+/// [`OverQuota`].
+pub struct Throttle {
+    store: Arc<dyn QuotaStore>,
+    quotas: HashMap<Endpoint, Quota>,
+    regulatory: Option<Quota>,
+    over_quota: OverQuota,
+}
+
+impl Throttle {
+    /// Create a throttle enforcing the regulatory limit and rejecting over-quota calls
+    pub fn new(store: Arc<dyn QuotaStore>) -> Self {
+        Self {
+            store,
+            quotas: HashMap::new(),
+            regulatory: Some(Quota::PSD2_REGULATORY),
+            over_quota: OverQuota::Reject,
+        }
+    }
+
+    /// Limit the calls to an endpoint per bank, consent and account
+    pub fn with_quota(mut self, endpoint: Endpoint, quota: Quota) -> Self {
+        self.quotas.insert(endpoint, quota);
+        self
+    }
+
+    /// Set the limit of AIS calls made without the PSU, or `None` to not enforce it
+    pub fn with_regulatory_limit(mut self, quota: Option<Quota>) -> Self {
+        self.regulatory = quota;
+        self
+    }
+
+    /// Set what happens to over-quota calls
+    pub fn with_over_quota(mut self, over_quota: OverQuota) -> Self {
+        self.over_quota = over_quota;
+        self
+    }
+
+    /// Get the quota store
+    pub fn store(&self) -> &dyn QuotaStore {
+        self.store.as_ref()
+    }
+
+    /// Count a call against its quotas, waiting or failing if one is used up
+    ///
+    /// The call is counted against all of its quotas or, if one is used up, none.
+    pub async fn acquire(&self, call: &ThrottledCall<'_>) -> Result<()> {
+        let mut quotas = Vec::new();
+        if let Some(quota) = self.regulatory.filter(|_| call.is_regulated()) {
+            quotas.push((call.key("psd2Regulatory"), quota));
+        }
+        if let Some(quota) = self.quotas.get(&call.endpoint) {
+            quotas.push((call.key(call.endpoint.operation()), *quota));
+        }
+        if quotas.is_empty() {
+            return Ok(());
+        }
+
+        loop {
+            let now = Utc::now();
+            let slots: Vec<QuotaSlot<'_>> = quotas
+                .iter()
+                .map(|(key, quota)| QuotaSlot { key, since: quota.since(now), limit: quota.limit })
+                .collect();
+            if self.store.try_acquire(&slots, now).await? {
+                return Ok(());
+            }
+
+            // The call can be made once every used-up quota has room again
+            let mut resets_at = now;
+            for (slot, (_, quota)) in slots.iter().zip(&quotas) {
+                let calls = self.store.calls(slot.key, slot.since).await?;
+                if calls.len() >= slot.limit as usize {
+                    resets_at = resets_at.max(quota.resets_at(calls));
+                }
+            }
+            let wait = (resets_at - now).to_std().unwrap_or(Duration::ZERO);
+            match self.over_quota {
+                OverQuota::Queue { max_wait } if wait <= max_wait => {
+                    tracing::info!("Quota for {} used up, waiting {:?}", call.endpoint, wait);
+                    tokio::time::sleep(wait).await;
+                }
+                _ => {
+                    return Err(PolishApiError::QuotaExceeded {
+                        endpoint: call.endpoint,
+                        resets_at,
+                    })
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for Throttle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Throttle")
+            .field("quotas", &self.quotas)
+            .field("regulatory", &self.regulatory)
+            .field("over_quota", &self.over_quota)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, token_body};
+    use crate::types::{GetAccountRequest, RequestHeaders};
+    use crate::utils::HeadersBuilder;
+    use crate::{InMemoryTokenStore, StoredToken, TokenManager, TokenStore};
+
+    const ACCOUNT: &str = "PL61109010140000071219812874";
+
+    fn call(endpoint: Endpoint, account_number: &str, is_direct_psu: bool) -> ThrottledCall<'_> {
+        ThrottledCall { endpoint, aspsp: "bank", consent_id: Some("consent"), account_number: Some(account_number), is_direct_psu }
+    }
+
+    #[tokio::test]
+    async fn test_regulatory_limit() {
+        let throttle = Throttle::new(Arc::new(InMemoryQuotaStore::new()));
+        for _ in 0..4 {
+            throttle.acquire(&call(Endpoint::GetTransactionsDone, ACCOUNT, false)).await.unwrap();
+        }
+
+        let result = throttle.acquire(&call(Endpoint::GetAccount, ACCOUNT, false)).await;
+        assert!(matches!(result, Err(PolishApiError::QuotaExceeded { endpoint: Endpoint::GetAccount, resets_at }) if resets_at > Utc::now()));
+
+        // Calls with the PSU and calls for other accounts are not affected
+        throttle.acquire(&call(Endpoint::GetAccount, ACCOUNT, true)).await.unwrap();
+        throttle.acquire(&call(Endpoint::GetAccount, "PL27114020040000300201355387", false)).await.unwrap();
+        throttle.acquire(&call(Endpoint::DeleteConsent, ACCOUNT, false)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_quota_queues() {
+        let throttle = Throttle::new(Arc::new(InMemoryQuotaStore::new()))
+            .with_quota(Endpoint::Confirmation, Quota::new(1, Duration::from_secs(1)))
+            .with_over_quota(OverQuota::Queue { max_wait: Duration::from_secs(2) });
+
+        let first = call(Endpoint::Confirmation, ACCOUNT, true);
+        throttle.acquire(&first).await.unwrap();
+        let started = Utc::now();
+        throttle.acquire(&first).await.unwrap();
+        assert!(Utc::now() - started >= chrono::Duration::milliseconds(900));
+
+        let rejecting = throttle.with_over_quota(OverQuota::Queue { max_wait: Duration::ZERO });
+        assert!(matches!(rejecting.acquire(&first).await, Err(PolishApiError::QuotaExceeded { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_call_uses_no_quota() {
+        let store = Arc::new(InMemoryQuotaStore::new());
+        let throttle = Throttle::new(store.clone()).with_quota(Endpoint::GetAccount, Quota::per_day(1));
+        let call = call(Endpoint::GetAccount, ACCOUNT, false);
+        let since = Quota::PSD2_REGULATORY.since(Utc::now());
+
+        throttle.acquire(&call).await.unwrap();
+        for _ in 0..3 {
+            let result = throttle.acquire(&call).await;
+            assert!(matches!(result, Err(PolishApiError::QuotaExceeded { endpoint: Endpoint::GetAccount, .. })));
+        }
+
+        // Only the call that was let through counts against the regulatory limit
+        assert_eq!(store.calls(&call.key("psd2Regulatory"), since).await.unwrap().len(), 1);
+        for _ in 0..3 {
+            throttle.acquire(&ThrottledCall { endpoint: Endpoint::GetHolds, ..call }).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_daily_limit_is_rolling() {
+        let store = Arc::new(InMemoryQuotaStore::new());
+        let throttle = Throttle::new(store.clone());
+        let call = call(Endpoint::GetAccount, ACCOUNT, false);
+        let key = call.key("psd2Regulatory");
+
+        // Four calls late on the previous day count until 24 hours after each of them
+        let now = Utc::now();
+        let earlier = now - chrono::Duration::hours(23);
+        for minute in 0..4 {
+            let at = earlier + chrono::Duration::minutes(minute);
+            let slot = QuotaSlot { key: &key, since: Quota::PSD2_REGULATORY.since(at), limit: 4 };
+            assert!(store.try_acquire(&[slot], at).await.unwrap());
+        }
+
+        match throttle.acquire(&call).await {
+            Err(PolishApiError::QuotaExceeded { resets_at, .. }) => {
+                assert_eq!(resets_at, earlier + chrono::Duration::hours(24));
+            }
+            other => panic!("expected quota exceeded, got {:?}", other),
+        }
+
+        // Calls that left the period are forgotten
+        let later = now + chrono::Duration::hours(2);
+        let since = Quota::PSD2_REGULATORY.since(later);
+        assert!(store.calls(&key, since).await.unwrap().is_empty());
+        assert!(store.try_acquire(&[QuotaSlot { key: &key, since, limit: 4 }], later).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_file_store_survives_restart() {
+        let path = std::env::temp_dir().join(format!("polishapi-quota-{}.json", uuid::Uuid::new_v4()));
+        let now = Utc::now();
+        let since = Quota::PSD2_REGULATORY.since(now);
+        let slot = QuotaSlot { key: "key", since, limit: 2 };
+
+        let store = FileQuotaStore::new(&path);
+        assert!(store.try_acquire(&[slot], now).await.unwrap());
+        assert!(store.try_acquire(&[slot], now).await.unwrap());
+
+        let restarted = FileQuotaStore::new(&path);
+        assert_eq!(restarted.calls("key", since).await.unwrap(), [now, now]);
+        assert!(!restarted.try_acquire(&[slot], now).await.unwrap());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_file_store_drops_stale_counters() {
+        let path = std::env::temp_dir().join(format!("polishapi-quota-{}.json", uuid::Uuid::new_v4()));
+        let store = FileQuotaStore::new(&path);
+        let now = Utc::now();
+        let earlier = now - chrono::Duration::hours(2);
+        let hourly = |key, at: DateTime<Utc>| QuotaSlot { key, since: at - chrono::Duration::hours(1), limit: 1 };
+
+        assert!(store.try_acquire(&[hourly("stale", earlier)], earlier).await.unwrap());
+        assert!(store.try_acquire(&[hourly("current", now)], now).await.unwrap());
+
+        let counters: HashMap<String, QuotaCounter> = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(counters.keys().collect::<Vec<_>>(), ["current"]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_client_enforces_regulatory_limit() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v3_0.1/accounts/v3_0.1/getAccount")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "requestHeader": { "isDirectPsu": false }
+            })))
+            .with_status(400)
+            .expect(4)
+            .create_async()
+            .await;
+        let client = test_support::client(test_support::config(&server))
+            .await
+            .with_throttle(Throttle::new(Arc::new(InMemoryQuotaStore::new())));
+
+        let headers = HeadersBuilder::new().authorization("token").direct_psu(false).build();
+        for attempt in 0..5 {
+            let request = GetAccountRequest { request_header: Default::default(), account_number: ACCOUNT.to_string() };
+            let result = client.accounts().get_account(request, RequestHeaders { x_request_id: uuid::Uuid::new_v4(), ..headers.clone() }).await;
+            match attempt {
+                4 => assert!(matches!(result, Err(PolishApiError::QuotaExceeded { .. }))),
+                _ => assert!(matches!(result, Err(PolishApiError::Api { .. }))),
+            }
+        }
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_client_counts_explicit_consents_separately() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v3_0.1/accounts/v3_0.1/getAccount")
+            .with_status(400)
+            .expect(8)
+            .create_async()
+            .await;
+        let client = test_support::client(test_support::config(&server))
+            .await
+            .with_throttle(Throttle::new(Arc::new(InMemoryQuotaStore::new())));
+
+        for consent_id in ["consent-1", "consent-2"] {
+            let headers = HeadersBuilder::new().authorization("token").consent_id(consent_id).direct_psu(false).build();
+            for attempt in 0..5 {
+                let request = GetAccountRequest { request_header: Default::default(), account_number: ACCOUNT.to_string() };
+                let result = client.accounts().get_account(request, RequestHeaders { x_request_id: uuid::Uuid::new_v4(), ..headers.clone() }).await;
+                match attempt {
+                    4 => assert!(matches!(result, Err(PolishApiError::QuotaExceeded { .. }))),
+                    _ => assert!(matches!(result, Err(PolishApiError::Api { .. }))),
+                }
+            }
+        }
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_token_refresh_keeps_consent_quota() {
+        let mut server = mockito::Server::new_async().await;
+        let accounts = server
+            .mock("POST", "/v3_0.1/accounts/v3_0.1/getAccount")
+            .with_status(400)
+            .expect(4)
+            .create_async()
+            .await;
+        let refresh = server
+            .mock("POST", "/v3_0.1/auth/v3_0.1/token")
+            .with_body(token_body("second"))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let token = |expires_in| StoredToken {
+            access_token: "first".to_string(),
+            token_type: "Bearer".to_string(),
+            refresh_token: Some("refresh".to_string()),
+            scope: "ais".to_string(),
+            expires_at: Utc::now() + chrono::Duration::seconds(expires_in),
+            consent_id: Some("consent-1".to_string()),
+        };
+        let store = Arc::new(InMemoryTokenStore::new());
+        store.save(&token(3600)).await.unwrap();
+        let client = test_support::client(test_support::config(&server))
+            .await
+            .with_token_manager(TokenManager::new(store.clone()))
+            .with_throttle(Throttle::new(Arc::new(InMemoryQuotaStore::new())));
+
+        for attempt in 0..5 {
+            if attempt == 2 {
+                // The token is about to expire and is refreshed before the third call
+                store.save(&token(10)).await.unwrap();
+            }
+            let request = GetAccountRequest { request_header: Default::default(), account_number: ACCOUNT.to_string() };
+            let headers = HeadersBuilder::new().direct_psu(false).build();
+            let result = client.accounts().get_account(request, headers).await;
+            match attempt {
+                4 => assert!(matches!(result, Err(PolishApiError::QuotaExceeded { .. }))),
+                _ => assert!(matches!(result, Err(PolishApiError::Api { .. }))),
+            }
+        }
+
+        let stored = store.load().await.unwrap().unwrap();
+        assert_eq!(stored.access_token, "second");
+        assert_eq!(stored.consent_id.as_deref(), Some("consent-1"));
+        accounts.assert_async().await;
+        refresh.assert_async().await;
+    }
+}
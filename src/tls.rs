@@ -0,0 +1,335 @@
+use std::fmt;
+
+use reqwest::ClientBuilder;
+
+#[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+use crate::crypto::{Pkcs12Bundle, PrivateKey};
+use crate::types::{PolishApiError, Result};
+
+/// Client certificate used for mutual TLS with the ASPSP (eIDAS QWAC)
+#[derive(Clone)]
+pub enum ClientIdentity {
+    /// PEM-encoded certificate chain (leaf first) and matching private key
+    Pem {
+        certificate_chain: Vec<u8>,
+        private_key: Vec<u8>,
+        passphrase: Option<String>,
+    },
+    /// DER-encoded PKCS#12 bundle protected with a password
+    Pkcs12 {
+        der: Vec<u8>,
+        password: String,
+    },
+}
+
+impl ClientIdentity {
+    /// Create an identity from a PEM certificate chain and PEM private key
+    pub fn from_pem(certificate_chain: impl Into<Vec<u8>>, private_key: impl Into<Vec<u8>>) -> Self {
+        Self::Pem {
+            certificate_chain: certificate_chain.into(),
+            private_key: private_key.into(),
+            passphrase: None,
+        }
+    }
+
+    /// Create an identity from a PEM certificate chain and an `ENCRYPTED PRIVATE KEY`
+    pub fn from_encrypted_pem(
+        certificate_chain: impl Into<Vec<u8>>,
+        private_key: impl Into<Vec<u8>>,
+        passphrase: impl Into<String>,
+    ) -> Self {
+        Self::Pem {
+            certificate_chain: certificate_chain.into(),
+            private_key: private_key.into(),
+            passphrase: Some(passphrase.into()),
+        }
+    }
+
+    /// Create an identity from a DER-encoded PKCS#12 (`.p12` / `.pfx`) bundle
+    pub fn from_pkcs12_der(der: impl Into<Vec<u8>>, password: impl Into<String>) -> Self {
+        Self::Pkcs12 {
+            der: der.into(),
+            password: password.into(),
+        }
+    }
+
+    /// Load the private key (as PKCS#8) and the PEM certificate chain
+    #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+    fn load(&self) -> Result<(PrivateKey, Vec<u8>)> {
+        match self {
+            Self::Pem { certificate_chain, private_key, passphrase } => {
+                let key = match passphrase {
+                    Some(passphrase) => PrivateKey::from_encrypted_pem(private_key, passphrase)?,
+                    None => PrivateKey::from_pem(private_key)?,
+                };
+                Ok((key, certificate_chain.clone()))
+            }
+            Self::Pkcs12 { der, password } => {
+                let bundle = Pkcs12Bundle::from_der(der, password)?;
+                let chain = bundle.certificate_chain_pem().into_bytes();
+                Ok((bundle.private_key, chain))
+            }
+        }
+    }
+
+    /// Convert the identity into a rustls-backed reqwest identity
+    #[cfg(feature = "rustls-tls")]
+    fn to_reqwest_identity(&self) -> Result<reqwest::Identity> {
+        let (key, chain) = self.load()?;
+        let mut bundle = key.to_pem().into_bytes();
+        bundle.extend_from_slice(&chain);
+
+        reqwest::Identity::from_pem(&bundle)
+            .map_err(|e| PolishApiError::Config(format!("Invalid client identity: {}", e)))
+    }
+
+    /// Convert the identity into a native-tls-backed reqwest identity
+    #[cfg(all(feature = "native-tls", not(feature = "rustls-tls")))]
+    fn to_reqwest_identity(&self) -> Result<reqwest::Identity> {
+        let (key, chain) = self.load()?;
+
+        reqwest::Identity::from_pkcs8_pem(&chain, key.to_pem().as_bytes())
+            .map_err(|e| PolishApiError::Config(format!("Invalid client identity: {}", e)))
+    }
+}
+
+impl fmt::Debug for ClientIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print key material
+        match self {
+            Self::Pem { .. } => f.write_str("ClientIdentity::Pem(<redacted>)"),
+            Self::Pkcs12 { .. } => f.write_str("ClientIdentity::Pkcs12(<redacted>)"),
+        }
+    }
+}
+
+/// Apply client identity and custom root certificates to an HTTP client builder
+#[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+pub(crate) fn configure(
+    mut builder: ClientBuilder,
+    identity: Option<&ClientIdentity>,
+    root_certificates: &[Vec<u8>],
+) -> Result<ClientBuilder> {
+    #[cfg(feature = "rustls-tls")]
+    {
+        builder = builder.use_rustls_tls();
+    }
+
+    if let Some(identity) = identity {
+        builder = builder.identity(identity.to_reqwest_identity()?);
+    }
+
+    for pem_bundle in root_certificates {
+        let certificates = reqwest::Certificate::from_pem_bundle(pem_bundle)
+            .map_err(|e| PolishApiError::Config(format!("Invalid root certificate: {}", e)))?;
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    Ok(builder)
+}
+
+/// Without a TLS backend only plain configurations can be built
+#[cfg(not(any(feature = "rustls-tls", feature = "native-tls")))]
+pub(crate) fn configure(
+    builder: ClientBuilder,
+    identity: Option<&ClientIdentity>,
+    root_certificates: &[Vec<u8>],
+) -> Result<ClientBuilder> {
+    if identity.is_some() || !root_certificates.is_empty() {
+        return Err(PolishApiError::Config(
+            "TLS settings require the `rustls-tls` or `native-tls` feature".to_string(),
+        ));
+    }
+
+    Ok(builder)
+}
+
+#[cfg(all(test, feature = "rustls-tls"))]
+mod tests {
+    use super::*;
+    use crate::client::{Config, PolishApiClient};
+    use crate::types::KeyError;
+    use rcgen::{
+        BasicConstraints, CertificateParams, ExtendedKeyUsagePurpose, IsCa, Issuer, KeyPair,
+    };
+    use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+    use rustls::server::WebPkiClientVerifier;
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio_rustls::TlsAcceptor;
+
+    struct TestPki {
+        ca_pem: String,
+        ca_der: CertificateDer<'static>,
+        server_der: CertificateDer<'static>,
+        server_key: KeyPair,
+        client_pem: String,
+        client_der: Vec<u8>,
+        client_key: KeyPair,
+    }
+
+    fn test_pki() -> TestPki {
+        let mut ca_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca_key = KeyPair::generate().unwrap();
+        let ca_cert = ca_params.self_signed(&ca_key).unwrap();
+        let issuer = Issuer::new(ca_params, ca_key);
+
+        let server_params = CertificateParams::new(vec!["localhost".to_string()]).unwrap();
+        let server_key = KeyPair::generate().unwrap();
+        let server_cert = server_params.signed_by(&server_key, &issuer).unwrap();
+
+        let mut client_params = CertificateParams::new(vec!["tpp.example.com".to_string()]).unwrap();
+        client_params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ClientAuth];
+        let client_key = KeyPair::generate().unwrap();
+        let client_cert = client_params.signed_by(&client_key, &issuer).unwrap();
+
+        TestPki {
+            ca_pem: ca_cert.pem(),
+            ca_der: ca_cert.der().clone(),
+            server_der: server_cert.der().clone(),
+            server_key,
+            client_pem: client_cert.pem(),
+            client_der: client_cert.der().to_vec(),
+            client_key,
+        }
+    }
+
+    /// Start a TLS server that requires a client certificate issued by the test CA
+    async fn start_mtls_server(pki: &TestPki) -> u16 {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let mut roots = rustls::RootCertStore::empty();
+        roots.add(pki.ca_der.clone()).unwrap();
+
+        let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+            .build()
+            .unwrap();
+        let server_key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(pki.server_key.serialize_der()));
+        let server_config = rustls::ServerConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_client_cert_verifier(verifier)
+            .with_single_cert(vec![pki.server_der.clone()], server_key)
+            .unwrap();
+
+        let acceptor = TlsAcceptor::from(Arc::new(server_config));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let acceptor = acceptor.clone();
+                tokio::spawn(async move {
+                    let Ok(mut tls) = acceptor.accept(stream).await else {
+                        return;
+                    };
+                    let mut buf = [0u8; 4096];
+                    let _ = tls.read(&mut buf).await;
+                    let _ = tls
+                        .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok")
+                        .await;
+                    let _ = tls.shutdown().await;
+                });
+            }
+        });
+
+        port
+    }
+
+    async fn get(config: Config, port: u16) -> Result<String> {
+        let client = PolishApiClient::new(config).await?;
+        let response = client
+            .http_client()
+            .get(format!("https://localhost:{}/", port))
+            .send()
+            .await?;
+        Ok(response.text().await?)
+    }
+
+    #[tokio::test]
+    async fn test_mtls_handshake_with_pem_identity() {
+        let pki = test_pki();
+        let port = start_mtls_server(&pki).await;
+
+        let config = Config::new(&format!("https://localhost:{}", port))
+            .unwrap()
+            .with_root_certificate_pem(pki.ca_pem.as_bytes())
+            .with_client_identity(ClientIdentity::from_pem(
+                pki.client_pem.as_bytes(),
+                pki.client_key.serialize_pem().as_bytes(),
+            ));
+
+        assert_eq!(get(config, port).await.unwrap(), "ok");
+    }
+
+    #[tokio::test]
+    async fn test_mtls_handshake_with_pkcs12_identity() {
+        let pki = test_pki();
+        let port = start_mtls_server(&pki).await;
+
+        let key = p12_keystore::PrivateKey::from_der(&pki.client_key.serialize_der()).unwrap();
+        let cert = p12_keystore::Certificate::from_der(&pki.client_der).unwrap();
+        let chain = p12_keystore::PrivateKeyChain::new(b"tpp".to_vec(), key, vec![cert]);
+        let mut keystore = p12_keystore::KeyStore::new();
+        keystore.add_entry("tpp", p12_keystore::KeyStoreEntry::PrivateKeyChain(chain));
+        let p12 = keystore.writer("secret").write().unwrap();
+
+        let config = Config::new(&format!("https://localhost:{}", port))
+            .unwrap()
+            .with_root_certificate_pem(pki.ca_pem.as_bytes())
+            .with_client_identity(ClientIdentity::from_pkcs12_der(p12, "secret"));
+
+        assert_eq!(get(config, port).await.unwrap(), "ok");
+    }
+
+    #[tokio::test]
+    async fn test_handshake_fails_without_client_certificate() {
+        let pki = test_pki();
+        let port = start_mtls_server(&pki).await;
+
+        let config = Config::new(&format!("https://localhost:{}", port))
+            .unwrap()
+            .with_root_certificate_pem(pki.ca_pem.as_bytes());
+
+        assert!(get(config, port).await.is_err());
+    }
+
+    #[test]
+    fn test_invalid_pkcs12_is_rejected() {
+        let identity = ClientIdentity::from_pkcs12_der(b"not a bundle".to_vec(), "secret");
+        assert!(matches!(identity.to_reqwest_identity(), Err(PolishApiError::Key(KeyError::Pkcs12(_)))));
+    }
+
+    #[test]
+    fn test_encrypted_pem_identity() {
+        let pki = test_pki();
+        let key = PrivateKey::from_pem(pki.client_key.serialize_pem()).unwrap();
+        let encrypted = pkcs8::PrivateKeyInfo::try_from(key.pkcs8_der())
+            .unwrap()
+            .encrypt_with_params(
+                pkcs8::pkcs5::pbes2::Parameters::pbkdf2_sha256_aes256cbc(2048, b"salt-1234", &[7u8; 16]).unwrap(),
+                "secret",
+            )
+            .unwrap();
+        let encrypted_pem = pem::encode(&pem::Pem::new("ENCRYPTED PRIVATE KEY", encrypted.as_bytes().to_vec()));
+
+        let identity = ClientIdentity::from_encrypted_pem(pki.client_pem.as_bytes(), encrypted_pem.as_bytes(), "secret");
+        assert!(identity.to_reqwest_identity().is_ok());
+
+        let identity = ClientIdentity::from_pem(pki.client_pem.as_bytes(), encrypted_pem.as_bytes());
+        assert!(matches!(
+            identity.to_reqwest_identity(),
+            Err(PolishApiError::Key(KeyError::PassphraseRequired))
+        ));
+    }
+
+    #[test]
+    fn test_debug_does_not_leak_key_material() {
+        let identity = ClientIdentity::from_pem(b"cert".to_vec(), b"very-secret-key".to_vec());
+        assert!(!format!("{:?}", identity).contains("very-secret-key"));
+    }
+}
@@ -0,0 +1,452 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex, RwLock};
+
+use crate::client::PolishApiClient;
+use crate::types::{PolishApiError, RequestHeaders, Result, TokenRequest, TokenResponse};
+
+/// Access token persisted by a [`TokenStore`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredToken {
+    pub access_token: String,
+    pub token_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    pub scope: String,
+    /// Time the access token expires, computed from `expires_in`
+    pub expires_at: DateTime<Utc>,
+    /// Consent the token was issued for, from `scope_details`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub consent_id: Option<String>,
+}
+
+impl StoredToken {
+    /// Build a stored token from a token endpoint response received now
+    ///
+    /// Lifetimes beyond the range of `DateTime` are clamped to its maximum.
+    pub fn from_response(response: &TokenResponse) -> Self {
+        let expires_at = i64::try_from(response.expires_in)
+            .ok()
+            .and_then(chrono::TimeDelta::try_seconds)
+            .and_then(|lifetime| Utc::now().checked_add_signed(lifetime))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        Self {
+            access_token: response.access_token.clone(),
+            token_type: response.token_type.clone(),
+            refresh_token: response.refresh_token.clone(),
+            scope: response.scope.clone(),
+            expires_at,
+            consent_id: response.scope_details.as_ref().map(|scope_details| scope_details.consent_id.clone()),
+        }
+    }
+
+    /// Whether the access token expires within `margin` from now
+    pub fn expires_within(&self, margin: Duration) -> bool {
+        let margin = chrono::Duration::from_std(margin).unwrap_or(chrono::Duration::MAX);
+        Utc::now().checked_add_signed(margin).is_none_or(|deadline| self.expires_at <= deadline)
+    }
+
+    /// Whether the access token has already expired
+    pub fn is_expired(&self) -> bool {
+        self.expires_within(Duration::ZERO)
+    }
+}
+
+/// Storage backend for the access and refresh tokens of a client
+///
+/// Implement this trait to share tokens between processes, e.g. in a
+/// database or a secrets manager.
+#[async_trait]
+pub trait TokenStore: Send + Sync {
+    /// Load the current token, if any
+    async fn load(&self) -> Result<Option<StoredToken>>;
+
+    /// Replace the current token
+    async fn save(&self, token: &StoredToken) -> Result<()>;
+
+    /// Remove the current token
+    async fn clear(&self) -> Result<()>;
+}
+
+/// Token store that keeps the token in memory
+#[derive(Debug, Default)]
+pub struct InMemoryTokenStore {
+    token: RwLock<Option<StoredToken>>,
+}
+
+impl InMemoryTokenStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl TokenStore for InMemoryTokenStore {
+    async fn load(&self) -> Result<Option<StoredToken>> {
+        Ok(self.token.read().await.clone())
+    }
+
+    async fn save(&self, token: &StoredToken) -> Result<()> {
+        *self.token.write().await = Some(token.clone());
+        Ok(())
+    }
+
+    async fn clear(&self) -> Result<()> {
+        *self.token.write().await = None;
+        Ok(())
+    }
+}
+
+/// Token store that keeps the token in a JSON file
+///
+/// The file is replaced atomically and, on Unix, is only readable by its owner.
+#[derive(Debug, Clone)]
+pub struct FileTokenStore {
+    path: PathBuf,
+}
+
+impl FileTokenStore {
+    /// Create a store backed by the file at `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Path of the token file
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+}
+
+#[async_trait]
+impl TokenStore for FileTokenStore {
+    async fn load(&self) -> Result<Option<StoredToken>> {
+        match tokio::fs::read(&self.path).await {
+            Ok(contents) => Ok(Some(serde_json::from_slice(&contents)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn save(&self, token: &StoredToken) -> Result<()> {
+        let contents = serde_json::to_vec_pretty(token)?;
+        let mut temp = self.path.clone().into_os_string();
+        temp.push(".tmp");
+
+        let mut options = tokio::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let mut file = options.open(&temp).await?;
+        tokio::io::AsyncWriteExt::write_all(&mut file, &contents).await?;
+        file.sync_all().await?;
+
+        tokio::fs::rename(&temp, &self.path).await?;
+        Ok(())
+    }
+
+    async fn clear(&self) -> Result<()> {
+        match tokio::fs::remove_file(&self.path).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Keeps the access token of a client fresh
+///
+/// Save the token of the PSU session to manage with [`TokenManager::store_response`].
+/// Before each AIS, PIS and CAF call the token is refreshed with the
+/// `refresh_token` grant once it is within the refresh margin of its expiry,
+/// and injected as the bearer `Authorization` header. Concurrent callers share
+/// a single refresh request.
+pub struct TokenManager {
+    store: std::sync::Arc<dyn TokenStore>,
+    refresh_margin: Duration,
+    refresh_lock: Mutex<()>,
+}
+
+impl TokenManager {
+    /// Create a manager backed by the given store, refreshing 60 seconds before expiry
+    pub fn new(store: std::sync::Arc<dyn TokenStore>) -> Self {
+        Self {
+            store,
+            refresh_margin: Duration::from_secs(60),
+            refresh_lock: Mutex::new(()),
+        }
+    }
+
+    /// Set how long before expiry the token is refreshed
+    pub fn with_refresh_margin(mut self, margin: Duration) -> Self {
+        self.refresh_margin = margin;
+        self
+    }
+
+    /// Get the token store
+    pub fn store(&self) -> &dyn TokenStore {
+        self.store.as_ref()
+    }
+
+    /// Manage the token of a token endpoint response, replacing the stored one
+    ///
+    /// Responses from [`crate::AuthService::token`] are not saved automatically,
+    /// so exchanged tokens or logins of other PSUs never replace the managed token.
+    pub async fn store_response(&self, response: &TokenResponse) -> Result<()> {
+        self.store.save(&StoredToken::from_response(response)).await
+    }
+
+    /// Consent the stored token was issued for, if `access_token` is that token
+    pub async fn consent_id(&self, access_token: &str) -> Result<Option<String>> {
+        Ok(self
+            .store
+            .load()
+            .await?
+            .filter(|token| token.access_token == access_token)
+            .and_then(|token| token.consent_id))
+    }
+
+    /// Return a valid access token, refreshing it first if it is about to expire
+    ///
+    /// Returns `None` if the store holds no token.
+    pub async fn access_token(&self, client: &PolishApiClient) -> Result<Option<String>> {
+        match self.store.load().await? {
+            Some(token) if !token.expires_within(self.refresh_margin) => return Ok(Some(token.access_token)),
+            None => return Ok(None),
+            Some(_) => {}
+        }
+
+        let _guard = self.refresh_lock.lock().await;
+
+        // Another caller may have refreshed the token while we were waiting
+        let token = match self.store.load().await? {
+            Some(token) => token,
+            None => return Ok(None),
+        };
+        if !token.expires_within(self.refresh_margin) {
+            return Ok(Some(token.access_token));
+        }
+
+        let refresh_token = match token.refresh_token {
+            Some(refresh_token) => refresh_token,
+            None if token.is_expired() => {
+                return Err(PolishApiError::Authentication {
+                    message: "access token expired and no refresh token is available".to_string(),
+                })
+            }
+            None => return Ok(Some(token.access_token)),
+        };
+
+        let request = TokenRequest::refresh_token(&client.config().client_id, &refresh_token);
+        let result = client.auth().token(request, RequestHeaders::default()).await;
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = client.metrics() {
+            metrics.record_token_refresh(client.aspsp(), result.is_ok());
+        }
+
+        // Refresh responses usually omit the refresh token and consent of the session
+        let mut refreshed = StoredToken::from_response(&result?);
+        refreshed.refresh_token = refreshed.refresh_token.or(Some(refresh_token));
+        refreshed.consent_id = refreshed.consent_id.or(token.consent_id);
+        self.store.save(&refreshed).await?;
+        Ok(Some(refreshed.access_token))
+    }
+}
+
+impl std::fmt::Debug for TokenManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenManager")
+            .field("refresh_margin", &self.refresh_margin)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, response_body, token_body};
+    use std::sync::Arc;
+
+    fn token(access_token: &str, expires_in: i64, refresh_token: Option<&str>) -> StoredToken {
+        StoredToken {
+            access_token: access_token.to_string(),
+            token_type: "Bearer".to_string(),
+            refresh_token: refresh_token.map(str::to_owned),
+            scope: "ais".to_string(),
+            expires_at: Utc::now() + chrono::Duration::seconds(expires_in),
+            consent_id: None,
+        }
+    }
+
+    async fn client(server: &mockito::Server, store: Arc<dyn TokenStore>) -> PolishApiClient {
+        test_support::client(test_support::config(server))
+            .await
+            .with_token_manager(TokenManager::new(store))
+    }
+
+    #[test]
+    fn test_expires_within() {
+        let token = token("a", 30, None);
+        assert!(token.expires_within(Duration::from_secs(60)));
+        assert!(!token.expires_within(Duration::from_secs(10)));
+        assert!(!token.is_expired());
+    }
+
+    #[test]
+    fn test_huge_expires_in_is_clamped() {
+        for expires_in in [u64::MAX, i64::MAX as u64, 1 << 44] {
+            let response: TokenResponse = serde_json::from_value(serde_json::json!({
+                "responseHeader": { "requestId": uuid::Uuid::new_v4() },
+                "access_token": "a",
+                "token_type": "Bearer",
+                "expires_in": expires_in.to_string(),
+                "scope": "ais"
+            }))
+            .unwrap();
+
+            let token = StoredToken::from_response(&response);
+            assert_eq!(token.expires_at, DateTime::<Utc>::MAX_UTC);
+            assert!(!token.expires_within(Duration::from_secs(3600)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_store_round_trip() {
+        let path = std::env::temp_dir().join(format!("polishapi-token-{}.json", uuid::Uuid::new_v4()));
+        let store = FileTokenStore::new(&path);
+        assert_eq!(store.load().await.unwrap(), None);
+
+        let token = token("a", 3600, Some("r"));
+        store.save(&token).await.unwrap();
+        assert_eq!(store.load().await.unwrap(), Some(token));
+
+        store.clear().await.unwrap();
+        assert_eq!(store.load().await.unwrap(), None);
+        store.clear().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_valid_token_is_not_refreshed() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server.mock("POST", "/v3_0.1/auth/v3_0.1/token").expect(0).create_async().await;
+
+        let store = Arc::new(InMemoryTokenStore::new());
+        store.save(&token("current", 3600, Some("refresh"))).await.unwrap();
+        let client = client(&server, store).await;
+
+        let access_token = client.token_manager().unwrap().access_token(&client).await.unwrap();
+        assert_eq!(access_token.as_deref(), Some("current"));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_refresh_is_single_flight() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v3_0.1/auth/v3_0.1/token")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "grant_type": "refresh_token",
+                "refresh_token": "refresh"
+            })))
+            .with_body(token_body("fresh"))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let store = Arc::new(InMemoryTokenStore::new());
+        store.save(&token("stale", 10, Some("refresh"))).await.unwrap();
+        let client = client(&server, store.clone()).await;
+        let manager = client.token_manager().unwrap();
+
+        let (a, b, c) = tokio::join!(
+            manager.access_token(&client),
+            manager.access_token(&client),
+            manager.access_token(&client),
+        );
+        for access_token in [a, b, c] {
+            assert_eq!(access_token.unwrap().as_deref(), Some("fresh"));
+        }
+        mock.assert_async().await;
+
+        // The refresh token is kept when the response omits it
+        let stored = store.load().await.unwrap().unwrap();
+        assert_eq!(stored.refresh_token.as_deref(), Some("refresh"));
+        assert!(!stored.expires_within(Duration::from_secs(3000)));
+    }
+
+    #[tokio::test]
+    async fn test_exchanged_token_does_not_replace_managed_token() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v3_0.1/auth/v3_0.1/token")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({ "grant_type": "exchange_token" })))
+            .with_body(token_body("exchanged"))
+            .create_async()
+            .await;
+
+        let store = Arc::new(InMemoryTokenStore::new());
+        let managed = StoredToken { consent_id: Some("consent-1".to_string()), ..token("current", 3600, Some("refresh")) };
+        store.save(&managed).await.unwrap();
+        let client = client(&server, store.clone()).await;
+
+        let scope_details = crate::types::ScopeDetails::builder(crate::types::ScopeGroupType::Ais, "consent-2")
+            .valid_for(chrono::Duration::days(1))
+            .privilege(
+                crate::types::PrivilegeList::for_account("PL61109010140000071219812874")
+                    .with_get_transactions_done(crate::types::ScopeUsageLimit::Multiple, 90),
+            )
+            .build()
+            .unwrap();
+        let response = client
+            .auth()
+            .exchange_token("current", scope_details, RequestHeaders::default())
+            .await
+            .unwrap();
+        assert_eq!(response.access_token, "exchanged");
+        mock.assert_async().await;
+        assert_eq!(store.load().await.unwrap(), Some(managed));
+
+        // Saving a new session does not inherit the previous one
+        client.token_manager().unwrap().store_response(&response).await.unwrap();
+        let stored = store.load().await.unwrap().unwrap();
+        assert_eq!(stored.access_token, "exchanged");
+        assert_eq!(stored.refresh_token, None);
+        assert_eq!(stored.consent_id, None);
+    }
+
+    #[tokio::test]
+    async fn test_expired_token_without_refresh_token() {
+        let server = mockito::Server::new_async().await;
+        let store = Arc::new(InMemoryTokenStore::new());
+        store.save(&token("stale", -10, None)).await.unwrap();
+        let client = client(&server, store).await;
+
+        let result = client.token_manager().unwrap().access_token(&client).await;
+        assert!(matches!(result, Err(PolishApiError::Authentication { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_bearer_header_is_injected() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v3_0.1/accounts/v3_0.1/getAccounts")
+            .match_header("Authorization", "Bearer current")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "requestHeader": { "token": "current" }
+            })))
+            .with_body(response_body(r#""accounts":[]"#))
+            .create_async()
+            .await;
+
+        let store = Arc::new(InMemoryTokenStore::new());
+        store.save(&token("current", 3600, None)).await.unwrap();
+        let client = client(&server, store).await;
+        let request = crate::types::GetAccountsRequest { request_header: Default::default(), page_id: None, per_page: None };
+
+        client.accounts().get_accounts(request, RequestHeaders::default()).await.unwrap();
+        mock.assert_async().await;
+    }
+}
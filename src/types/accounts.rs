@@ -1,209 +1,290 @@
-use chrono::NaiveDate;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
 
 use super::common::{
-    BaseRequest, Amount, Balance, TransactionStatus,
-    ConsentStatus, AccountReference, RemittanceInformation, Links
+    Bank, DictionaryItem, NameAddress, PageInfo, ResponseHeader,
+    SenderRecipient, TransactionCategory,
 };
+use super::request_header::RequestHeaderAis;
 
-/// Account information
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Account {
-    pub resource_id: String,
-    pub iban: Option<String>,
-    pub bban: Option<String>,
-    pub pan: Option<String>,
-    pub masked_pan: Option<String>,
-    pub msisdn: Option<String>,
+/// Relation between the PSU and an account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountPsuRelation {
+    pub type_of_relation: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_of_proxy: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stake: Option<u32>,
+}
+
+/// Basic account information returned by getAccounts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountBaseInfo {
+    pub account_number: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_type_name: Option<String>,
+    pub account_type: DictionaryItem,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub psu_relations: Option<Vec<AccountPsuRelation>>,
+}
+
+/// Type of the account holder
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AccountHolderType {
+    Individual,
+    Corporation,
+}
+
+/// Full account information returned by getAccount
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountInfo {
+    pub account_number: String,
+    pub name_address: NameAddress,
+    pub account_type: DictionaryItem,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_type_name: Option<String>,
+    pub account_holder_type: AccountHolderType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_name_client: Option<String>,
     pub currency: String,
-    pub name: Option<String>,
-    pub product: Option<String>,
-    pub cash_account_type: Option<String>,
-    pub status: Option<String>,
-    pub bic: Option<String>,
-    pub linked_accounts: Option<String>,
-    pub usage: Option<String>,
-    pub details: Option<String>,
-    pub balances: Option<Vec<Balance>>,
-    #[serde(rename = "_links")]
-    pub links: Option<Links>,
+    pub available_balance: String,
+    pub booking_balance: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bank: Option<Bank>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aux_data: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub psu_relations: Option<Vec<AccountPsuRelation>>,
 }
 
 /// Get accounts request
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct GetAccountsRequest {
-    #[serde(flatten)]
-    pub base: BaseRequest,
-    pub with_balance: Option<bool>,
+    pub request_header: RequestHeaderAis,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<u32>,
 }
 
 /// Get accounts response
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct GetAccountsResponse {
-    #[serde(rename = "requestId")]
-    pub request_id: Uuid,
-    pub accounts: Vec<Account>,
-    #[serde(rename = "_links")]
-    pub links: Option<Links>,
+    pub response_header: ResponseHeader,
+    pub accounts: Vec<AccountBaseInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_info: Option<PageInfo>,
 }
 
 /// Get account request
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct GetAccountRequest {
-    #[serde(flatten)]
-    pub base: BaseRequest,
-    pub account_id: String,
-    pub with_balance: Option<bool>,
+    pub request_header: RequestHeaderAis,
+    pub account_number: String,
 }
 
 /// Get account response
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct GetAccountResponse {
-    #[serde(rename = "requestId")]
-    pub request_id: Uuid,
-    pub account: Account,
-    #[serde(rename = "_links")]
-    pub links: Option<Links>,
+    pub response_header: ResponseHeader,
+    pub account: AccountInfo,
 }
 
 /// Transaction information
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Transaction {
-    pub transaction_id: Option<String>,
-    pub entry_reference: Option<String>,
-    pub end_to_end_id: Option<String>,
-    pub mandate_id: Option<String>,
-    pub check_id: Option<String>,
-    pub creditor_id: Option<String>,
-    pub booking_date: Option<NaiveDate>,
-    pub value_date: Option<NaiveDate>,
-    pub transaction_amount: Amount,
-    pub currency_exchange: Option<Vec<CurrencyExchange>>,
-    pub creditor_name: Option<String>,
-    pub creditor_account: Option<AccountReference>,
-    pub creditor_agent: Option<String>,
-    pub ultimate_creditor: Option<String>,
-    pub debtor_name: Option<String>,
-    pub debtor_account: Option<AccountReference>,
-    pub debtor_agent: Option<String>,
-    pub ultimate_debtor: Option<String>,
-    pub remittance_information_unstructured: Option<String>,
-    pub remittance_information_structured: Option<RemittanceInformation>,
-    pub additional_information: Option<String>,
-    pub purpose_code: Option<String>,
-    pub bank_transaction_code: Option<String>,
-    pub proprietary_bank_transaction_code: Option<String>,
-    #[serde(rename = "_links")]
-    pub links: Option<Links>,
-}
-
-/// Currency exchange information
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CurrencyExchange {
-    pub source_currency: String,
-    pub target_currency: String,
-    pub exchange_rate: String,
-    pub unit_currency: Option<String>,
-    pub contract_identification: Option<String>,
-    pub quotation_date: Option<NaiveDate>,
+///
+/// Shared by done, pending, rejected, cancelled and scheduled transactions and
+/// by holds; fields not used by a list are absent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionInfo {
+    pub item_id: String,
+    pub amount: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trade_date: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mcc: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aux_data: Option<serde_json::Value>,
+    pub transaction_category: TransactionCategory,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_status: Option<DictionaryItem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initiator: Option<NameAddress>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sender: Option<SenderRecipient>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipient: Option<SenderRecipient>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub booking_date: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_transaction_balance: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejection_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejection_date: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hold_expiration_date: Option<DateTime<Utc>>,
 }
 
+/// Hold (blocked funds) information
+pub type HoldInfo = TransactionInfo;
+
 /// Get transactions request
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct GetTransactionsRequest {
-    #[serde(flatten)]
-    pub base: BaseRequest,
-    pub account_id: String,
-    pub booking_status: Option<TransactionStatus>,
-    pub date_from: Option<NaiveDate>,
-    pub date_to: Option<NaiveDate>,
-    pub entry_reference_from: Option<String>,
-    pub entry_reference_to: Option<String>,
-    pub delta_list: Option<bool>,
+    pub request_header: RequestHeaderAis,
+    pub account_number: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_id_from: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_date_from: Option<chrono::NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_date_to: Option<chrono::NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub booking_date_from: Option<chrono::NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub booking_date_to: Option<chrono::NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_amount: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_amount: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<u32>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub transaction_category: Option<TransactionCategory>,
 }
 
 /// Get transactions response
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct GetTransactionsResponse {
-    #[serde(rename = "requestId")]
-    pub request_id: Uuid,
-    pub account: AccountReference,
-    pub transactions: TransactionList,
-    #[serde(rename = "_links")]
-    pub links: Option<Links>,
-}
-
-/// Transaction list container
-#[derive(Debug, Serialize, Deserialize)]
-pub struct TransactionList {
-    pub booked: Option<Vec<Transaction>>,
-    pub pending: Option<Vec<Transaction>>,
-    #[serde(rename = "_links")]
-    pub links: Option<Links>,
+    pub response_header: ResponseHeader,
+    #[serde(default)]
+    pub transactions: Vec<TransactionInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_info: Option<PageInfo>,
 }
 
 /// Get transaction detail request
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct GetTransactionDetailRequest {
-    #[serde(flatten)]
-    pub base: BaseRequest,
-    pub account_id: String,
-    pub transaction_id: String,
+    pub request_header: RequestHeaderAis,
+    pub item_id: String,
+    pub account_number: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub booking_date: Option<DateTime<Utc>>,
 }
 
-/// Get transaction detail response
-#[derive(Debug, Serialize, Deserialize)]
-pub struct GetTransactionDetailResponse {
-    #[serde(rename = "requestId")]
-    pub request_id: Uuid,
-    pub transactions_details: Transaction,
-    #[serde(rename = "_links")]
-    pub links: Option<Links>,
+/// Currency conversion rate applied to a transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrencyRate {
+    pub rate: f64,
+    pub from_currency: String,
+    pub to_currency: String,
+}
+
+/// Card used for a transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CardInfo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub card_holder: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub card_number: Option<String>,
 }
 
-/// Account holds information
+/// Get transaction detail response
 #[derive(Debug, Serialize, Deserialize)]
-pub struct Hold {
-    pub hold_id: String,
-    pub hold_amount: Amount,
-    pub hold_date: NaiveDate,
-    pub expiry_date: Option<NaiveDate>,
-    pub additional_information: Option<String>,
+#[serde(rename_all = "camelCase")]
+pub struct GetTransactionDetailResponse {
+    pub response_header: ResponseHeader,
+    pub base_info: TransactionInfo,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zus_info: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub us_info: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub card_info: Option<CardInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency_date: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_rate: Option<Vec<CurrencyRate>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_currency: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount_base_currency: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub used_payment_instrument_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tpp_transaction_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tpp_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejection_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hold_expiration_date: Option<DateTime<Utc>>,
 }
 
 /// Get holds request
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct GetHoldsRequest {
-    #[serde(flatten)]
-    pub base: BaseRequest,
-    pub account_id: String,
+    pub request_header: RequestHeaderAis,
+    pub account_number: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_id_from: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_date_from: Option<chrono::NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_date_to: Option<chrono::NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_amount: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_amount: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<u32>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub transaction_category: Option<TransactionCategory>,
 }
 
 /// Get holds response
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct GetHoldsResponse {
-    #[serde(rename = "requestId")]
-    pub request_id: Uuid,
-    pub account: AccountReference,
-    pub holds: Vec<Hold>,
-    #[serde(rename = "_links")]
-    pub links: Option<Links>,
+    pub response_header: ResponseHeader,
+    #[serde(default)]
+    pub holds: Vec<HoldInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_info: Option<PageInfo>,
 }
 
 /// Delete consent request
+///
+/// The ASPSP answers with `204 No Content`.
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct DeleteConsentRequest {
-    #[serde(flatten)]
-    pub base: BaseRequest,
+    pub request_header: RequestHeaderAis,
     pub consent_id: String,
 }
-
-/// Delete consent response
-#[derive(Debug, Serialize, Deserialize)]
-pub struct DeleteConsentResponse {
-    #[serde(rename = "requestId")]
-    pub request_id: Uuid,
-    pub consent_status: ConsentStatus,
-}
-
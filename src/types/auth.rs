@@ -1,90 +1,348 @@
-use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
 
-use super::common::BaseRequest;
+use super::common::{lenient, string_or_number, ResponseHeader};
+use super::errors::{PolishApiError, Result};
+use super::request_header::RequestHeaderAs;
+use super::scope::ScopeDetails;
+
+// The Authorization Service keeps the OAuth2 snake_case field names of the
+// specification; only the header objects are camelCase.
 
 /// OAuth2 authorization request
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AuthorizeRequest {
-    #[serde(flatten)]
-    pub base: BaseRequest,
+    #[serde(rename = "requestHeader")]
+    pub request_header: RequestHeaderAs,
     pub response_type: String,
     pub client_id: String,
     pub redirect_uri: String,
     pub scope: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope_details: Option<ScopeDetails>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub state: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub code_challenge: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub code_challenge_method: Option<String>,
 }
 
+impl AuthorizeRequest {
+    /// Check the `scope_details`, if any
+    pub fn validate(&self) -> Result<()> {
+        match &self.scope_details {
+            Some(scope_details) => scope_details.validate(),
+            None => Ok(()),
+        }
+    }
+}
+
 /// OAuth2 authorization response
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AuthorizeResponse {
-    #[serde(rename = "requestId")]
-    pub request_id: Uuid,
-    pub authorization_url: String,
-    pub state: Option<String>,
+    #[serde(rename = "responseHeader")]
+    pub response_header: ResponseHeader,
+    #[serde(rename = "aspspRedirectUri")]
+    pub aspsp_redirect_uri: String,
 }
 
 /// External authorization tool code request
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EatCodeRequest {
-    #[serde(flatten)]
-    pub base: BaseRequest,
-    pub client_id: String,
+    #[serde(rename = "requestHeader")]
+    pub request_header: RequestHeaderAs,
+    pub response_type: String,
+    #[serde(rename = "eatCode")]
     pub eat_code: String,
-    pub redirect_uri: String,
+    #[serde(rename = "eatType", skip_serializing_if = "Option::is_none")]
+    pub eat_type: Option<String>,
+    pub client_id: String,
+    pub scope: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope_details: Option<ScopeDetails>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+}
+
+impl EatCodeRequest {
+    /// Check the `scope_details`, if any
+    pub fn validate(&self) -> Result<()> {
+        match &self.scope_details {
+            Some(scope_details) => scope_details.validate(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// OAuth2 grant used at the token endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GrantType {
+    AuthorizationCode,
+    RefreshToken,
+    /// Exchange an access token for one with a different scope
+    ExchangeToken,
+}
+
+/// How the client authenticates at the token endpoint
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientAuthMethod {
+    /// `client_secret` in the request body
+    #[default]
+    ClientSecretPost,
+    /// `client_assertion` JWT signed with the JWS key (RFC 7523)
+    PrivateKeyJwt,
+    /// Mutual TLS with the QWAC client certificate (RFC 8705)
+    TlsClientAuth,
+}
+
+impl ClientAuthMethod {
+    /// Value of the `token_endpoint_auth_method` client metadata
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClientAuthMethod::ClientSecretPost => "client_secret_post",
+            ClientAuthMethod::PrivateKeyJwt => "private_key_jwt",
+            ClientAuthMethod::TlsClientAuth => "tls_client_auth",
+        }
+    }
 }
 
 /// OAuth2 token request
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenRequest {
-    #[serde(flatten)]
-    pub base: BaseRequest,
-    pub grant_type: String,
+    #[serde(rename = "requestHeader")]
+    pub request_header: RequestHeaderAs,
+    pub grant_type: GrantType,
+    #[serde(rename = "Code", skip_serializing_if = "Option::is_none")]
     pub code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub redirect_uri: Option<String>,
     pub client_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub client_secret: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_assertion_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_assertion: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub code_verifier: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub refresh_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exchange_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope_details: Option<ScopeDetails>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_user_session: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_ip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+}
+
+impl TokenRequest {
+    /// Create a token request with only the grant type and client ID set
+    pub fn new(grant_type: GrantType, client_id: impl Into<String>) -> Self {
+        Self {
+            request_header: Default::default(),
+            grant_type,
+            code: None,
+            redirect_uri: None,
+            client_id: client_id.into(),
+            client_secret: None,
+            client_assertion_type: None,
+            client_assertion: None,
+            code_verifier: None,
+            refresh_token: None,
+            exchange_token: None,
+            scope: None,
+            scope_details: None,
+            is_user_session: None,
+            user_ip: None,
+            user_agent: None,
+        }
+    }
+
+    /// Create an `authorization_code` request
+    pub fn authorization_code(client_id: impl Into<String>, code: impl Into<String>) -> Self {
+        Self {
+            code: Some(code.into()),
+            ..Self::new(GrantType::AuthorizationCode, client_id)
+        }
+    }
+
+    /// Create a `refresh_token` request
+    pub fn refresh_token(client_id: impl Into<String>, refresh_token: impl Into<String>) -> Self {
+        Self {
+            refresh_token: Some(refresh_token.into()),
+            ..Self::new(GrantType::RefreshToken, client_id)
+        }
+    }
+
+    /// Create an `exchange_token` request trading `exchange_token` for a token with the given scope
+    pub fn exchange_token(
+        client_id: impl Into<String>,
+        exchange_token: impl Into<String>,
+        scope_details: ScopeDetails,
+    ) -> Self {
+        Self {
+            exchange_token: Some(exchange_token.into()),
+            scope: Some(scope_details.scope_group_type.as_str().to_string()),
+            scope_details: Some(scope_details),
+            ..Self::new(GrantType::ExchangeToken, client_id)
+        }
+    }
+
+    /// Check that the fields required by the grant are present
+    pub fn validate(&self) -> Result<()> {
+        let missing = match self.grant_type {
+            GrantType::AuthorizationCode if self.code.is_none() => Some("Code"),
+            GrantType::RefreshToken if self.refresh_token.is_none() => Some("refresh_token"),
+            GrantType::ExchangeToken if self.exchange_token.is_none() => Some("exchange_token"),
+            _ => None,
+        };
+        if let Some(field) = missing {
+            return Err(PolishApiError::Validation(format!(
+                "{} is required for the {:?} grant",
+                field, self.grant_type
+            )));
+        }
+        match &self.scope_details {
+            Some(scope_details) => scope_details.validate(),
+            None => Ok(()),
+        }
+    }
 }
 
 /// OAuth2 token response
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TokenResponse {
-    #[serde(rename = "requestId")]
-    pub request_id: Uuid,
+    #[serde(rename = "responseHeader")]
+    pub response_header: ResponseHeader,
     pub access_token: String,
     pub token_type: String,
+    /// Token lifetime in seconds (sent as a string by the specification)
+    #[serde(with = "string_or_number")]
     pub expires_in: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub refresh_token: Option<String>,
     pub scope: String,
+    /// Consent the token was issued for; `None` (with a warning logged) if the
+    /// bank's `scope_details` do not match the schema
+    #[serde(default, deserialize_with = "lenient::deserialize", skip_serializing_if = "Option::is_none")]
+    pub scope_details: Option<ScopeDetails>,
 }
 
-/// Registration request
-#[derive(Debug, Serialize, Deserialize)]
-pub struct RegisterRequest {
-    #[serde(flatten)]
-    pub base: BaseRequest,
+/// JSON Web Key (RFC 7517) of a TPP signing key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Jwk {
+    pub kty: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kid: Option<String>,
+    #[serde(rename = "use", skip_serializing_if = "Option::is_none")]
+    pub key_use: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alg: Option<String>,
+    /// RSA modulus
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<String>,
+    /// RSA public exponent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub e: Option<String>,
+    /// EC curve
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crv: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y: Option<String>,
+    /// Certificate chain (standard base64 DER, leaf first)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x5c: Option<Vec<String>>,
+}
+
+/// JSON Web Key Set
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Jwks {
+    pub keys: Vec<Jwk>,
+}
+
+/// Client metadata (RFC 7591 section 2)
+///
+/// Sent in the registration request and signed into the software statement.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClientMetadata {
     pub client_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub client_uri: Option<String>,
     pub redirect_uris: Vec<String>,
     pub grant_types: Vec<String>,
     pub response_types: Vec<String>,
     pub scope: String,
     pub token_endpoint_auth_method: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contacts: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jwks: Option<Jwks>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jwks_uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logo_uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policy_uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tos_uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub software_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub software_version: Option<String>,
 }
 
-/// Registration response
+/// Dynamic client registration request (RFC 7591)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RegisterRequest {
+    #[serde(rename = "requestHeader")]
+    pub request_header: RequestHeaderAs,
+    #[serde(flatten)]
+    pub metadata: ClientMetadata,
+    /// Signed JWT asserting the metadata (RFC 7591 section 2.3)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub software_statement: Option<String>,
+}
+
+/// Dynamic client registration response (RFC 7591)
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RegisterResponse {
-    #[serde(rename = "requestId")]
-    pub request_id: Uuid,
+    #[serde(rename = "responseHeader", skip_serializing_if = "Option::is_none")]
+    pub response_header: Option<ResponseHeader>,
     pub client_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub client_secret: Option<String>,
-    pub client_id_issued_at: Option<DateTime<Utc>>,
-    pub client_secret_expires_at: Option<DateTime<Utc>>,
+    /// Seconds since the Unix epoch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id_issued_at: Option<i64>,
+    /// Seconds since the Unix epoch, 0 if the secret does not expire
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_secret_expires_at: Option<i64>,
 }
 
+/// Notification the ASPSP sends to the `callbackURL` of a decoupled authorization
+///
+/// Carries the authorization code once the PSU approves in the External
+/// Authorization Tool, or an OAuth2 error if the authorization failed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EatCallbackNotification {
+    #[serde(rename = "responseHeader", skip_serializing_if = "Option::is_none")]
+    pub response_header: Option<ResponseHeader>,
+    #[serde(rename = "Code", alias = "code", skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_description: Option<String>,
+}
@@ -3,6 +3,9 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 /// Common request headers for PolishAPI
+///
+/// Besides the HTTP headers this carries the per-call values copied into the
+/// `requestHeader` of the body: the PSU context, callback URL and API key.
 #[derive(Debug, Clone)]
 pub struct RequestHeaders {
     pub authorization: String,
@@ -11,6 +14,25 @@ pub struct RequestHeaders {
     pub accept_charset: String,
     pub x_jws_signature: String,
     pub x_request_id: Uuid,
+    pub psu: PsuContext,
+    pub callback_url: Option<String>,
+    pub api_key: Option<String>,
+    /// Consent the call is made under, keying client-side quotas; not sent to the bank
+    pub consent_id: Option<String>,
+}
+
+/// Information about the PSU forwarded to the ASPSP in the `requestHeader`
+#[derive(Debug, Clone, Default)]
+pub struct PsuContext {
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub is_company_context: Option<bool>,
+    pub identifier_type: Option<String>,
+    pub identifier_value: Option<String>,
+    pub context_identifier_type: Option<String>,
+    pub context_identifier_value: Option<String>,
+    /// Whether the PSU takes part in AIS calls (`isDirectPsu`)
+    pub is_direct_psu: Option<bool>,
 }
 
 impl Default for RequestHeaders {
@@ -22,163 +44,147 @@ impl Default for RequestHeaders {
             accept_charset: "utf-8".to_string(),
             x_jws_signature: String::new(),
             x_request_id: Uuid::new_v4(),
+            psu: PsuContext::default(),
+            callback_url: None,
+            api_key: None,
+            consent_id: None,
         }
     }
 }
 
-/// Base request structure with common fields
-#[derive(Debug, Serialize, Deserialize)]
-pub struct BaseRequest {
-    #[serde(rename = "requestId")]
-    pub request_id: Uuid,
-}
-
-/// Base response structure with common fields
-#[derive(Debug, Serialize, Deserialize)]
-pub struct BaseResponse {
-    #[serde(rename = "requestId")]
+/// `responseHeader` returned in every PolishAPI response body
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseHeader {
     pub request_id: Uuid,
-    #[serde(rename = "timestamp")]
-    pub timestamp: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_date: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_callback: Option<bool>,
 }
 
-/// Account identifier
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AccountId {
-    pub iban: Option<String>,
-    pub bban: Option<String>,
-    pub pan: Option<String>,
-    pub masked_pan: Option<String>,
-    pub msisdn: Option<String>,
+/// Name and address lines
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NameAddress {
+    pub value: Vec<String>,
 }
 
-/// Amount with currency
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Amount {
-    pub currency: String,
-    pub amount: String,
+/// Dictionary entry with a code and a description
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DictionaryItem {
+    pub code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
-/// Address information
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Address {
-    pub street_name: Option<String>,
-    pub building_number: Option<String>,
-    pub town_name: Option<String>,
-    pub post_code: Option<String>,
-    pub country: String,
+/// Bank information
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bank {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bic_or_swift: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<Vec<String>>,
+}
+
+/// Sender or recipient of a transaction
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SenderRecipient {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_number: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_mass_payment: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bank: Option<Bank>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_address: Option<NameAddress>,
+}
+
+/// Paging information
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageInfo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_page: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_page: Option<String>,
 }
 
-/// Transaction status enumeration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Direction of a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
-pub enum TransactionStatus {
-    Booked,
-    Pending,
-    Rejected,
-    Cancelled,
-    Scheduled,
+pub enum TransactionCategory {
+    Credit,
+    Debit,
 }
 
-/// Payment status enumeration
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "UPPERCASE")]
+/// General status of a payment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub enum PaymentStatus {
-    Received,
-    Pending,
-    Accepted,
-    AcceptedCustomerProfile,
-    AcceptedTechnicalValidation,
-    AcceptedWithChange,
-    Rejected,
+    Submitted,
+    InProgress,
     Cancelled,
-    Executed,
-}
-
-/// Consent status enumeration
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub enum ConsentStatus {
-    Received,
+    Done,
     Rejected,
-    Valid,
-    RevokedByPsy,
-    Expired,
-    TerminatedByTPP,
-}
-
-/// Frequency code for recurring transactions
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum FrequencyCode {
-    Daily,
-    Weekly,
-    EveryTwoWeeks,
-    Monthly,
-    EveryTwoMonths,
-    Quarterly,
-    SemiAnnual,
-    Annual,
-}
-
-/// Balance type enumeration
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub enum BalanceType {
-    ClosingBooked,
-    Expected,
-    Authorised,
-    OpeningBooked,
-    InterimAvailable,
-    InterimBooked,
-    ForwardAvailable,
-    NonInvoiced,
+    Scheduled,
 }
 
-/// Balance information
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Balance {
-    pub balance_amount: Amount,
-    pub balance_type: BalanceType,
-    pub credit_limit_included: Option<bool>,
-    pub last_change_date_time: Option<DateTime<Utc>>,
-    pub reference_date: Option<chrono::NaiveDate>,
-    pub last_committed_transaction: Option<String>,
-}
+/// Serde helpers for integers the specification transmits as strings
+///
+/// Serializes as a string and accepts both strings and numbers.
+pub(crate) mod string_or_number {
+    use serde::{de, Deserialize, Deserializer, Serializer};
 
-/// Creditor/Debtor account information
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AccountReference {
-    pub iban: Option<String>,
-    pub bban: Option<String>,
-    pub pan: Option<String>,
-    pub masked_pan: Option<String>,
-    pub msisdn: Option<String>,
-    pub currency: Option<String>,
-}
+    pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_string())
+    }
 
-/// Remittance information
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RemittanceInformation {
-    pub unstructured: Option<Vec<String>>,
-    pub structured: Option<Vec<StructuredRemittanceInformation>>,
-}
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Value {
+            Number(u64),
+            String(String),
+        }
 
-/// Structured remittance information
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StructuredRemittanceInformation {
-    pub reference: Option<String>,
-    pub reference_type: Option<String>,
-    pub reference_issuer: Option<String>,
+        match Value::deserialize(deserializer)? {
+            Value::Number(value) => Ok(value),
+            Value::String(value) => value.parse().map_err(de::Error::custom),
+        }
+    }
 }
 
-/// Links for HATEOAS navigation
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Links {
-    #[serde(rename = "self")]
-    pub self_link: Option<String>,
-    pub first: Option<String>,
-    pub prev: Option<String>,
-    pub next: Option<String>,
-    pub last: Option<String>,
+/// Serde helper for optional fields a bank may send in an unexpected shape
+///
+/// A value that does not deserialize is logged and dropped, so the rest of
+/// the response is still usable.
+pub(crate) mod lenient {
+    use serde::de::DeserializeOwned;
+    use serde::{Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: DeserializeOwned,
+    {
+        match Option::<serde_json::Value>::deserialize(deserializer)? {
+            None | Some(serde_json::Value::Null) => Ok(None),
+            Some(value) => match serde_json::from_value(value) {
+                Ok(parsed) => Ok(Some(parsed)),
+                Err(e) => {
+                    tracing::warn!("Ignoring {} that does not match the schema: {}", std::any::type_name::<T>(), e);
+                    Ok(None)
+                }
+            },
+        }
+    }
 }
-
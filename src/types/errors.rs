@@ -27,6 +27,22 @@ pub enum PolishApiError {
     #[error("Cryptographic operation failed: {0}")]
     Crypto(String),
 
+    /// JWS signature verification error
+    #[error("JWS verification failed: {0}")]
+    Jws(#[from] JwsError),
+
+    /// Private key or PKCS#12 bundle could not be loaded
+    #[error("Key loading failed: {0}")]
+    Key(#[from] KeyError),
+
+    /// Decoupled (External Authorization Tool) authorization did not complete
+    #[error("Decoupled authorization failed: {0}")]
+    Decoupled(#[from] DecoupledAuthError),
+
+    /// Response signature missing or invalid
+    #[error("Response signature verification failed: {0}")]
+    ResponseSignature(#[source] JwsError),
+
     /// Configuration error
     #[error("Configuration error: {0}")]
     Config(String),
@@ -35,6 +51,17 @@ pub enum PolishApiError {
     #[error("Validation error: {0}")]
     Validation(String),
 
+    /// A client-side quota does not allow the call before `resets_at`
+    #[error("Quota exceeded for {endpoint} until {resets_at}")]
+    QuotaExceeded {
+        endpoint: crate::profile::Endpoint,
+        resets_at: chrono::DateTime<chrono::Utc>,
+    },
+
+    /// Local I/O error, e.g. reading or writing a token store file
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
     /// Network timeout
     #[error("Request timeout")]
     Timeout,
@@ -44,6 +71,126 @@ pub enum PolishApiError {
     Internal(String),
 }
 
+impl PolishApiError {
+    /// Name of the variant in snake case, e.g. `quota_exceeded`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PolishApiError::Http(_) => "http",
+            PolishApiError::Json(_) => "json",
+            PolishApiError::Authentication { .. } => "authentication",
+            PolishApiError::Authorization { .. } => "authorization",
+            PolishApiError::Api { .. } => "api",
+            PolishApiError::Crypto(_) => "crypto",
+            PolishApiError::Jws(_) => "jws",
+            PolishApiError::Key(_) => "key",
+            PolishApiError::Decoupled(_) => "decoupled",
+            PolishApiError::ResponseSignature(_) => "response_signature",
+            PolishApiError::Config(_) => "config",
+            PolishApiError::Validation(_) => "validation",
+            PolishApiError::QuotaExceeded { .. } => "quota_exceeded",
+            PolishApiError::Io(_) => "io",
+            PolishApiError::Timeout => "timeout",
+            PolishApiError::Internal(_) => "internal",
+        }
+    }
+}
+
+/// Detailed reason a JWS signature was rejected
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum JwsError {
+    /// No signature was sent where one is required
+    #[error("missing signature")]
+    Missing,
+
+    /// The value is not a detached compact JWS (`header..signature`)
+    #[error("malformed detached JWS: {0}")]
+    Malformed(String),
+
+    /// The protected header could not be decoded or is missing required fields
+    #[error("invalid protected header: {0}")]
+    InvalidHeader(String),
+
+    /// The `alg` header names an algorithm we do not support
+    #[error("unsupported algorithm: {0}")]
+    UnsupportedAlgorithm(String),
+
+    /// `b64: false` is used without listing `b64` in `crit`
+    #[error("\"b64\" must be listed in the \"crit\" header")]
+    MissingCrit,
+
+    /// The `crit` header lists an extension we do not understand
+    #[error("unsupported critical header parameter: {0}")]
+    UnsupportedCritical(String),
+
+    /// The verification key cannot be used with the algorithm from the header
+    #[error("key type does not match algorithm {0}")]
+    KeyMismatch(String),
+
+    /// The verification key could not be parsed
+    #[error("invalid verification key: {0}")]
+    InvalidKey(String),
+
+    /// The signature does not match the payload
+    #[error("signature mismatch")]
+    SignatureMismatch,
+}
+
+/// Detailed reason a private key could not be loaded
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum KeyError {
+    /// The input is not valid PEM
+    #[error("invalid PEM: {0}")]
+    Pem(String),
+
+    /// No private key was found in the input
+    #[error("no private key found")]
+    MissingKey,
+
+    /// The key is stored in a format we do not support
+    #[error("unsupported key format: {0}")]
+    UnsupportedFormat(String),
+
+    /// The key is encrypted but no passphrase was given
+    #[error("private key is encrypted and requires a passphrase")]
+    PassphraseRequired,
+
+    /// The passphrase is wrong or the encryption scheme is unsupported
+    #[error("failed to decrypt private key: {0}")]
+    Decryption(String),
+
+    /// The key structure could not be decoded
+    #[error("invalid private key: {0}")]
+    InvalidKey(String),
+
+    /// The PKCS#12 bundle could not be opened
+    #[error("invalid PKCS#12 bundle: {0}")]
+    Pkcs12(String),
+}
+
+/// Reason a decoupled authorization did not produce a token
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DecoupledAuthError {
+    /// The PSU or the ASPSP rejected the authorization
+    #[error("authorization rejected: {0}")]
+    Rejected(String),
+
+    /// The authorization request expired at the ASPSP
+    #[error("authorization request expired")]
+    Expired,
+
+    /// The PSU did not complete the authorization in time
+    #[error("no decision within {0:?}")]
+    TimedOut(std::time::Duration),
+
+    /// The callback channel was closed before a notification arrived
+    #[error("callback channel closed")]
+    CallbackClosed,
+
+    /// The callback `state` does not match the request
+    #[error("callback state does not match the request")]
+    StateMismatch,
+}
+
 /// Result type alias for PolishAPI operations
 pub type Result<T> = std::result::Result<T, PolishApiError>;
 
@@ -1,24 +1,22 @@
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
 
-use super::common::{BaseRequest, Amount, AccountReference};
+use super::common::ResponseHeader;
+use super::request_header::RequestHeaderCaf;
 
 /// Confirmation of availability of funds request
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct FundsConfirmationRequest {
-    #[serde(flatten)]
-    pub base: BaseRequest,
-    pub card_number: Option<String>,
-    pub account: AccountReference,
-    pub payee: Option<String>,
-    pub instructed_amount: Amount,
+    pub request_header: RequestHeaderCaf,
+    pub account_number: String,
+    pub amount: String,
+    pub currency: String,
 }
 
 /// Confirmation of availability of funds response
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct FundsConfirmationResponse {
-    #[serde(rename = "requestId")]
-    pub request_id: Uuid,
+    pub response_header: ResponseHeader,
     pub funds_available: bool,
 }
-
@@ -1,15 +1,98 @@
 pub mod common;
+pub mod request_header;
 pub mod errors;
 pub mod auth;
 pub mod accounts;
 pub mod payments;
 pub mod funds;
+pub mod scope;
 
 // Re-export commonly used types
 pub use common::*;
-pub use errors::{PolishApiError, Result};
+pub use request_header::*;
+pub use errors::{DecoupledAuthError, JwsError, KeyError, PolishApiError, Result};
 pub use auth::*;
 pub use accounts::*;
 pub use payments::*;
 pub use funds::*;
+pub use scope::*;
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{de::DeserializeOwned, Serialize};
+
+    /// Deserialize a golden fixture and check it serializes back to the same JSON
+    fn round_trip<T: DeserializeOwned + Serialize>(fixture: &str) {
+        let expected: serde_json::Value = serde_json::from_str(fixture).unwrap();
+        let parsed: T = serde_json::from_str(fixture).unwrap();
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), expected);
+    }
+
+    macro_rules! fixture {
+        ($path:literal) => {
+            include_str!(concat!("../../testdata/fixtures/", $path))
+        };
+    }
+
+    #[test]
+    fn test_as_fixtures() {
+        round_trip::<AuthorizeRequest>(fixture!("as/authorize_request.json"));
+        round_trip::<AuthorizeResponse>(fixture!("as/authorize_response.json"));
+        round_trip::<EatCodeRequest>(fixture!("as/authorize_ext_request.json"));
+        round_trip::<TokenRequest>(fixture!("as/token_request.json"));
+        round_trip::<TokenResponse>(fixture!("as/token_response.json"));
+        round_trip::<RegisterRequest>(fixture!("as/register_request.json"));
+        round_trip::<RegisterResponse>(fixture!("as/register_response.json"));
+    }
+
+    #[test]
+    fn test_ais_fixtures() {
+        round_trip::<GetAccountsRequest>(fixture!("ais/get_accounts_request.json"));
+        round_trip::<GetAccountsResponse>(fixture!("ais/get_accounts_response.json"));
+        round_trip::<GetAccountRequest>(fixture!("ais/get_account_request.json"));
+        round_trip::<GetAccountResponse>(fixture!("ais/get_account_response.json"));
+        round_trip::<GetTransactionsRequest>(fixture!("ais/get_transactions_done_request.json"));
+        round_trip::<GetTransactionsResponse>(fixture!("ais/get_transactions_done_response.json"));
+        round_trip::<GetTransactionDetailRequest>(fixture!("ais/get_transaction_detail_request.json"));
+        round_trip::<GetTransactionDetailResponse>(fixture!("ais/get_transaction_detail_response.json"));
+        round_trip::<GetHoldsRequest>(fixture!("ais/get_holds_request.json"));
+        round_trip::<GetHoldsResponse>(fixture!("ais/get_holds_response.json"));
+        round_trip::<DeleteConsentRequest>(fixture!("ais/delete_consent_request.json"));
+    }
+
+    #[test]
+    fn test_pis_fixtures() {
+        round_trip::<DomesticPaymentRequest>(fixture!("pis/domestic_request.json"));
+        round_trip::<EeaPaymentRequest>(fixture!("pis/eea_request.json"));
+        round_trip::<NonEeaPaymentRequest>(fixture!("pis/non_eea_request.json"));
+        round_trip::<TaxPaymentRequest>(fixture!("pis/tax_request.json"));
+        round_trip::<PaymentInitiationResponse>(fixture!("pis/payment_response.json"));
+        round_trip::<PaymentStatusRequest>(fixture!("pis/get_payment_request.json"));
+        round_trip::<PaymentStatusResponse>(fixture!("pis/get_payment_response.json"));
+    }
+
+    #[test]
+    fn test_caf_fixtures() {
+        round_trip::<FundsConfirmationRequest>(fixture!("caf/confirmation_request.json"));
+        round_trip::<FundsConfirmationResponse>(fixture!("caf/confirmation_response.json"));
+    }
+
+    #[test]
+    fn test_expires_in_accepts_numbers() {
+        let body = fixture!("as/token_response.json").replace(r#""expires_in": "3600""#, r#""expires_in": 3600"#);
+        let response: TokenResponse = serde_json::from_str(&body).unwrap();
+        assert_eq!(response.expires_in, 3600);
+    }
+
+    #[test]
+    fn test_unknown_scope_details_do_not_fail_token_response() {
+        let response: TokenResponse = serde_json::from_str(fixture!("as/token_response_unknown_scope.json")).unwrap();
+        assert_eq!(response.access_token, "eyJhbGciOiJSUzI1NiJ9.access.token");
+        assert!(response.scope_details.is_none());
+
+        let response: TokenResponse = serde_json::from_str(fixture!("as/token_response.json")).unwrap();
+        assert_eq!(response.scope_details.unwrap().consent_id, "consent-8f2a");
+    }
+}
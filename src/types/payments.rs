@@ -1,196 +1,226 @@
-use chrono::{DateTime, Utc, NaiveDate};
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
 
-use super::common::{
-    BaseRequest, Amount, PaymentStatus, AccountReference,
-    RemittanceInformation, Address, Links
-};
+use super::common::{Bank, NameAddress, PaymentStatus, ResponseHeader};
+use super::request_header::RequestHeaderPis;
 
-/// Payment type enumeration
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub enum PaymentType {
-    Domestic,
-    Eea,
-    NonEea,
-    Tax,
+/// Payment delivery mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeliveryMode {
+    ExpressD0,
+    StandardD1,
+    UrgentD1,
+    StandardD2,
 }
 
-/// Payment product enumeration
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub enum PaymentProduct {
+/// Clearing system used to execute the payment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaymentSystem {
+    Elixir,
+    ExpressElixir,
+    Sorbnet,
+    BlueCash,
+    Internal,
+    #[serde(rename = "SEPA")]
     Sepa,
+    #[serde(rename = "InstantSEPA")]
     InstantSepa,
-    Target2,
-    CrossBorderCreditTransfer,
+    Target,
+    Swift,
+}
+
+/// Payment execution mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExecutionMode {
+    Immediate,
+    FutureDated,
+}
+
+/// Recipient of a payment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecipientPis {
+    pub account_number: String,
+    pub name_address: NameAddress,
+}
+
+/// Sender of a payment
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SenderPis {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_number: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_address: Option<NameAddress>,
+}
+
+/// Amount, currency and description of a transfer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferData {
+    pub description: String,
+    pub amount: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution_date: Option<NaiveDate>,
+    pub currency: String,
+}
+
+/// Split payment (MPP) details
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionInfoSp {
+    pub sp_invoice_number: String,
+    pub sp_tax_identification_number: String,
+    pub sp_tax_amount: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sp_description: Option<String>,
 }
 
 /// Domestic payment request
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct DomesticPaymentRequest {
-    #[serde(flatten)]
-    pub base: BaseRequest,
-    pub instructed_amount: Amount,
-    pub debtor_account: AccountReference,
-    pub creditor_name: String,
-    pub creditor_account: AccountReference,
-    pub creditor_agent: Option<String>,
-    pub creditor_address: Option<Address>,
-    pub ultimate_creditor: Option<String>,
-    pub debtor_name: Option<String>,
-    pub ultimate_debtor: Option<String>,
-    pub remittance_information_unstructured: Option<String>,
-    pub remittance_information_structured: Option<RemittanceInformation>,
-    pub requested_execution_date: Option<NaiveDate>,
-    pub requested_execution_time: Option<DateTime<Utc>>,
+    pub request_header: RequestHeaderPis,
+    pub recipient: RecipientPis,
+    pub sender: SenderPis,
+    pub transfer_data: TransferData,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delivery_mode: Option<DeliveryMode>,
+    pub system: PaymentSystem,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hold: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution_mode: Option<ExecutionMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub split_payment: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_info_sp: Option<TransactionInfoSp>,
+    pub tpp_transaction_id: String,
 }
 
 /// EEA payment request
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct EeaPaymentRequest {
-    #[serde(flatten)]
-    pub base: BaseRequest,
-    pub instructed_amount: Amount,
-    pub debtor_account: AccountReference,
-    pub creditor_name: String,
-    pub creditor_account: AccountReference,
-    pub creditor_agent: Option<String>,
-    pub creditor_address: Option<Address>,
-    pub ultimate_creditor: Option<String>,
-    pub debtor_name: Option<String>,
-    pub ultimate_debtor: Option<String>,
-    pub remittance_information_unstructured: Option<String>,
-    pub remittance_information_structured: Option<RemittanceInformation>,
-    pub requested_execution_date: Option<NaiveDate>,
-    pub requested_execution_time: Option<DateTime<Utc>>,
-    pub charge_bearer: Option<String>,
-    pub service_level: Option<String>,
-    pub category_purpose: Option<String>,
+    pub request_header: RequestHeaderPis,
+    pub recipient: RecipientPis,
+    pub sender: SenderPis,
+    pub transfer_data: TransferData,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delivery_mode: Option<DeliveryMode>,
+    pub system: PaymentSystem,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hold: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution_mode: Option<ExecutionMode>,
+    pub tpp_transaction_id: String,
 }
 
 /// Non-EEA payment request
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct NonEeaPaymentRequest {
-    #[serde(flatten)]
-    pub base: BaseRequest,
-    pub instructed_amount: Amount,
-    pub debtor_account: AccountReference,
-    pub creditor_name: String,
-    pub creditor_account: AccountReference,
-    pub creditor_agent: Option<String>,
-    pub creditor_address: Option<Address>,
-    pub ultimate_creditor: Option<String>,
-    pub debtor_name: Option<String>,
-    pub ultimate_debtor: Option<String>,
-    pub remittance_information_unstructured: Option<String>,
-    pub remittance_information_structured: Option<RemittanceInformation>,
-    pub requested_execution_date: Option<NaiveDate>,
-    pub requested_execution_time: Option<DateTime<Utc>>,
-    pub charge_bearer: Option<String>,
-    pub service_level: Option<String>,
-    pub category_purpose: Option<String>,
-    pub exchange_rate_information: Option<ExchangeRateInformation>,
+    pub request_header: RequestHeaderPis,
+    pub recipient: RecipientPis,
+    pub recipient_bank: Bank,
+    pub sender: SenderPis,
+    pub transfer_data: TransferData,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transfer_charges: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delivery_mode: Option<DeliveryMode>,
+    pub system: PaymentSystem,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hold: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution_mode: Option<ExecutionMode>,
+    pub tpp_transaction_id: String,
 }
 
-/// Tax payment request
-#[derive(Debug, Serialize, Deserialize)]
-pub struct TaxPaymentRequest {
-    #[serde(flatten)]
-    pub base: BaseRequest,
-    pub instructed_amount: Amount,
-    pub debtor_account: AccountReference,
-    pub creditor_name: String,
-    pub creditor_account: AccountReference,
-    pub creditor_agent: Option<String>,
-    pub tax_identification: TaxIdentification,
-    pub tax_period: Option<String>,
-    pub tax_type: Option<String>,
-    pub requested_execution_date: Option<NaiveDate>,
-}
-
-/// Tax identification information
-#[derive(Debug, Serialize, Deserialize)]
-pub struct TaxIdentification {
-    pub tax_identification_number: String,
-    pub tax_identification_type: String,
-    pub issuer: Option<String>,
+/// Payer identification for tax payments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayerInfo {
+    pub payor_id: String,
+    pub payor_id_type: String,
+}
+
+/// Tax office (US) payment details
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsInfo {
+    pub payer_info: PayerInfo,
+    pub form_code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub period_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub period_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub year: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub obligation_id: Option<String>,
 }
 
-/// Exchange rate information
+/// Tax payment request
 #[derive(Debug, Serialize, Deserialize)]
-pub struct ExchangeRateInformation {
-    pub unit_currency: String,
-    pub exchange_rate: Option<String>,
-    pub rate_type: Option<String>,
-    pub contract_identification: Option<String>,
+#[serde(rename_all = "camelCase")]
+pub struct TaxPaymentRequest {
+    pub request_header: RequestHeaderPis,
+    pub recipient: RecipientPis,
+    pub sender: SenderPis,
+    pub transfer_data: TransferData,
+    pub us_info: UsInfo,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delivery_mode: Option<DeliveryMode>,
+    pub system: PaymentSystem,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hold: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution_mode: Option<ExecutionMode>,
+    pub tpp_transaction_id: String,
 }
 
 /// Payment initiation response
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct PaymentInitiationResponse {
-    #[serde(rename = "requestId")]
-    pub request_id: Uuid,
-    pub transaction_status: PaymentStatus,
+    pub response_header: ResponseHeader,
     pub payment_id: String,
-    pub transaction_fees: Option<Amount>,
-    pub currency_conversion_fee: Option<Amount>,
-    pub estimated_total_amount: Option<Amount>,
-    pub estimated_interbank_settlement_amount: Option<Amount>,
-    #[serde(rename = "_links")]
-    pub links: Option<Links>,
-    pub psu_message: Option<String>,
+    pub general_status: PaymentStatus,
+    pub detailed_status: String,
 }
 
-/// Payment status request
+/// Payment status request (getPayment)
+///
+/// Identify the payment by `payment_id` or `tpp_transaction_id`.
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct PaymentStatusRequest {
-    #[serde(flatten)]
-    pub base: BaseRequest,
-    pub payment_id: String,
+    pub request_header: RequestHeaderPis,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tpp_transaction_id: Option<String>,
 }
 
-/// Payment status response
+/// Payment status response (getPayment)
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct PaymentStatusResponse {
-    #[serde(rename = "requestId")]
-    pub request_id: Uuid,
-    pub transaction_status: PaymentStatus,
-    pub funds_available: Option<bool>,
-    pub psu_message: Option<String>,
-    #[serde(rename = "_links")]
-    pub links: Option<Links>,
+    pub response_header: ResponseHeader,
+    pub payment_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tpp_transaction_id: Option<String>,
+    pub general_status: PaymentStatus,
+    pub detailed_status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution_mode: Option<ExecutionMode>,
 }
 
 /// Payment information request
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PaymentInformationRequest {
-    #[serde(flatten)]
-    pub base: BaseRequest,
-    pub payment_id: String,
-}
+#[deprecated(since = "0.2.0", note = "use `PaymentStatusRequest` with `get_payment_status`")]
+pub type PaymentInformationRequest = PaymentStatusRequest;
 
 /// Payment information response
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PaymentInformationResponse {
-    #[serde(rename = "requestId")]
-    pub request_id: Uuid,
-    pub payment_data: PaymentData,
-    pub transaction_status: PaymentStatus,
-    #[serde(rename = "_links")]
-    pub links: Option<Links>,
-}
-
-/// Payment data container
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PaymentData {
-    pub instructed_amount: Amount,
-    pub debtor_account: AccountReference,
-    pub creditor_name: String,
-    pub creditor_account: AccountReference,
-    pub creditor_agent: Option<String>,
-    pub remittance_information_unstructured: Option<String>,
-    pub requested_execution_date: Option<NaiveDate>,
-}
-
+#[deprecated(since = "0.2.0", note = "use `PaymentStatusResponse` with `get_payment_status`")]
+pub type PaymentInformationResponse = PaymentStatusResponse;
@@ -0,0 +1,303 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::Result;
+
+/// Common `requestHeader` fields sent in every PolishAPI request body
+///
+/// Unset fields are filled in by the client from [`crate::Config`] and the PSU
+/// context of [`super::RequestHeaders`] before the request is signed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestHeader {
+    /// Request identifier, equal to the `X-REQUEST-ID` HTTP header
+    pub request_id: Uuid,
+    /// User agent of the PSU's browser or application
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    /// IP address of the PSU
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip_address: Option<String>,
+    /// Time the request was sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_date: Option<DateTime<Utc>>,
+    /// TPP identifier
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tpp_id: Option<String>,
+    /// Whether the PSU acts on behalf of a company
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_company_context: Option<bool>,
+    /// Type of the PSU identifier
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub psu_identifier_type: Option<String>,
+    /// Value of the PSU identifier
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub psu_identifier_value: Option<String>,
+    /// Type of the identifier of the context the PSU acts in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub psu_context_identifier_type: Option<String>,
+    /// Value of the identifier of the context the PSU acts in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub psu_context_identifier_value: Option<String>,
+}
+
+/// `requestHeader` of Authorization Service requests (no access token)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestHeaderAs {
+    #[serde(flatten)]
+    pub base: RequestHeader,
+    /// URL the ASPSP notifies about the result of a decoupled authorization
+    #[serde(rename = "callbackURL", skip_serializing_if = "Option::is_none")]
+    pub callback_url: Option<String>,
+    /// Key the ASPSP sends back with callback notifications
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+}
+
+/// `requestHeader` of Account Information Service requests
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestHeaderAis {
+    #[serde(flatten)]
+    pub base: RequestHeader,
+    /// Access token
+    pub token: String,
+    /// URL the ASPSP notifies about asynchronous results
+    #[serde(rename = "callbackURL", skip_serializing_if = "Option::is_none")]
+    pub callback_url: Option<String>,
+    /// Key the ASPSP sends back with callback notifications
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// Whether the PSU takes part in the call; calls without the PSU count
+    /// against the `psd2Regulatory` limit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_direct_psu: Option<bool>,
+}
+
+/// `requestHeader` of Payment Initiation Service requests
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestHeaderPis {
+    #[serde(flatten)]
+    pub base: RequestHeader,
+    /// Access token
+    pub token: String,
+    /// URL the ASPSP notifies about payment status changes
+    #[serde(rename = "callbackURL", skip_serializing_if = "Option::is_none")]
+    pub callback_url: Option<String>,
+    /// Key the ASPSP sends back with callback notifications
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+}
+
+/// `requestHeader` of Confirmation of Availability of Funds requests
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestHeaderCaf {
+    #[serde(flatten)]
+    pub base: RequestHeader,
+    /// Access token
+    pub token: String,
+}
+
+/// Service-specific `requestHeader` variants
+pub trait ServiceRequestHeader {
+    /// Common fields shared by all variants
+    fn common_mut(&mut self) -> &mut RequestHeader;
+
+    /// Set the access token if the variant carries one and it is not set yet
+    fn set_token(&mut self, _token: &str) {}
+
+    /// Set the callback URL and API key if the variant supports them and they are not set yet
+    fn set_callback(&mut self, _callback_url: Option<&str>, _api_key: Option<&str>) {}
+
+    /// The `isDirectPsu` flag, if the variant carries one
+    fn direct_psu_mut(&mut self) -> Option<&mut Option<bool>> {
+        None
+    }
+}
+
+impl ServiceRequestHeader for RequestHeader {
+    fn common_mut(&mut self) -> &mut RequestHeader {
+        self
+    }
+}
+
+impl ServiceRequestHeader for RequestHeaderAs {
+    fn common_mut(&mut self) -> &mut RequestHeader {
+        &mut self.base
+    }
+
+    fn set_callback(&mut self, callback_url: Option<&str>, api_key: Option<&str>) {
+        fill(&mut self.callback_url, callback_url);
+        fill(&mut self.api_key, api_key);
+    }
+}
+
+impl ServiceRequestHeader for RequestHeaderAis {
+    fn common_mut(&mut self) -> &mut RequestHeader {
+        &mut self.base
+    }
+
+    fn set_token(&mut self, token: &str) {
+        fill_token(&mut self.token, token);
+    }
+
+    fn set_callback(&mut self, callback_url: Option<&str>, api_key: Option<&str>) {
+        fill(&mut self.callback_url, callback_url);
+        fill(&mut self.api_key, api_key);
+    }
+
+    fn direct_psu_mut(&mut self) -> Option<&mut Option<bool>> {
+        Some(&mut self.is_direct_psu)
+    }
+}
+
+impl ServiceRequestHeader for RequestHeaderPis {
+    fn common_mut(&mut self) -> &mut RequestHeader {
+        &mut self.base
+    }
+
+    fn set_token(&mut self, token: &str) {
+        fill_token(&mut self.token, token);
+    }
+
+    fn set_callback(&mut self, callback_url: Option<&str>, api_key: Option<&str>) {
+        fill(&mut self.callback_url, callback_url);
+        fill(&mut self.api_key, api_key);
+    }
+}
+
+impl ServiceRequestHeader for RequestHeaderCaf {
+    fn common_mut(&mut self) -> &mut RequestHeader {
+        &mut self.base
+    }
+
+    fn set_token(&mut self, token: &str) {
+        fill_token(&mut self.token, token);
+    }
+}
+
+/// Request bodies carrying a `requestHeader`
+pub trait PolishApiRequest {
+    /// The `requestHeader` variant used by the service
+    type Header: ServiceRequestHeader;
+
+    /// Mutable access to the `requestHeader`
+    fn request_header_mut(&mut self) -> &mut Self::Header;
+
+    /// Check the request before it is sent
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Set `target` to `value` unless the caller already provided one
+pub(crate) fn fill(target: &mut Option<String>, value: Option<&str>) {
+    if target.is_none() {
+        *target = value.map(str::to_owned);
+    }
+}
+
+fn fill_token(target: &mut String, token: &str) {
+    if target.is_empty() {
+        *target = token.to_string();
+    }
+}
+
+macro_rules! impl_polishapi_request {
+    // Requests with an inherent `validate` method
+    ($header:ty, validated => $($request:ty),+ $(,)?) => {
+        $(
+            impl PolishApiRequest for $request {
+                type Header = $header;
+
+                fn request_header_mut(&mut self) -> &mut Self::Header {
+                    &mut self.request_header
+                }
+
+                fn validate(&self) -> Result<()> {
+                    <$request>::validate(self)
+                }
+            }
+        )+
+    };
+    ($header:ty => $($request:ty),+ $(,)?) => {
+        $(
+            impl PolishApiRequest for $request {
+                type Header = $header;
+
+                fn request_header_mut(&mut self) -> &mut Self::Header {
+                    &mut self.request_header
+                }
+            }
+        )+
+    };
+}
+
+impl_polishapi_request!(RequestHeaderAs, validated =>
+    super::AuthorizeRequest, super::EatCodeRequest, super::TokenRequest,
+);
+impl_polishapi_request!(RequestHeaderAs => super::RegisterRequest);
+impl_polishapi_request!(RequestHeaderAis =>
+    super::GetAccountsRequest, super::GetAccountRequest, super::GetTransactionsRequest,
+    super::GetTransactionDetailRequest, super::GetHoldsRequest, super::DeleteConsentRequest,
+);
+impl_polishapi_request!(RequestHeaderPis =>
+    super::DomesticPaymentRequest, super::EeaPaymentRequest, super::NonEeaPaymentRequest,
+    super::TaxPaymentRequest, super::PaymentStatusRequest,
+);
+impl_polishapi_request!(RequestHeaderCaf => super::FundsConfirmationRequest);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_ais_header_serialization() {
+        let header = RequestHeaderAis {
+            base: RequestHeader {
+                request_id: Uuid::nil(),
+                tpp_id: Some("PSDPL-KNF-123".to_string()),
+                is_company_context: Some(false),
+                psu_identifier_type: Some("N".to_string()),
+                ..Default::default()
+            },
+            token: "access-token".to_string(),
+            callback_url: Some("https://tpp.example.com/callback".to_string()),
+            api_key: None,
+            is_direct_psu: Some(false),
+        };
+
+        assert_eq!(
+            serde_json::to_value(&header).unwrap(),
+            json!({
+                "requestId": "00000000-0000-0000-0000-000000000000",
+                "tppId": "PSDPL-KNF-123",
+                "isCompanyContext": false,
+                "psuIdentifierType": "N",
+                "token": "access-token",
+                "callbackURL": "https://tpp.example.com/callback",
+                "isDirectPsu": false
+            })
+        );
+    }
+
+    #[test]
+    fn test_fill_keeps_caller_values() {
+        let mut header = RequestHeaderPis {
+            token: "explicit".to_string(),
+            callback_url: Some("https://explicit".to_string()),
+            ..Default::default()
+        };
+        header.set_token("from-headers");
+        header.set_callback(Some("https://from-headers"), Some("key"));
+
+        assert_eq!(header.token, "explicit");
+        assert_eq!(header.callback_url.as_deref(), Some("https://explicit"));
+        assert_eq!(header.api_key.as_deref(), Some("key"));
+    }
+}
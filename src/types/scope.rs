@@ -0,0 +1,668 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::common::Bank;
+use super::errors::{PolishApiError, Result};
+use super::payments::{
+    DeliveryMode, DomesticPaymentRequest, EeaPaymentRequest, ExecutionMode, NonEeaPaymentRequest,
+    PaymentSystem, RecipientPis, SenderPis, TaxPaymentRequest, TransactionInfoSp, TransferData, UsInfo,
+};
+
+/// Longest transaction history (in days) a privilege may ask for
+pub const MAX_HISTORY_DAYS: u32 = 1460;
+
+/// Group of services a consent covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScopeGroupType {
+    /// Listing of the PSU's accounts only
+    #[serde(rename = "ais-accounts")]
+    AisAccounts,
+    #[serde(rename = "ais")]
+    Ais,
+    #[serde(rename = "pis")]
+    Pis,
+}
+
+impl ScopeGroupType {
+    /// Value of the OAuth2 `scope` parameter for this group
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScopeGroupType::AisAccounts => "ais-accounts",
+            ScopeGroupType::Ais => "ais",
+            ScopeGroupType::Pis => "pis",
+        }
+    }
+}
+
+/// How many times a privilege may be used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ScopeUsageLimit {
+    Single,
+    Multiple,
+}
+
+/// Throttling policy of a consent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThrottlingPolicy {
+    /// At most four PSU-unattended AIS calls per day (PSD2 RTS art. 36)
+    #[default]
+    #[serde(rename = "psd2Regulatory")]
+    Psd2Regulatory,
+}
+
+/// Privilege without parameters
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimplePrivilege {
+    pub scope_usage_limit: ScopeUsageLimit,
+}
+
+/// Privilege to a list of transactions or holds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryPrivilege {
+    pub scope_usage_limit: ScopeUsageLimit,
+    /// How many days back the history may be read, at most [`MAX_HISTORY_DAYS`]
+    pub max_allowed_history_long: u32,
+}
+
+/// Privilege to read the status of a payment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentPrivilege {
+    pub scope_usage_limit: ScopeUsageLimit,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tpp_transaction_id: Option<String>,
+}
+
+/// Privilege to initiate a domestic payment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DomesticTransferPrivilege {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope_usage_limit: Option<ScopeUsageLimit>,
+    pub recipient: RecipientPis,
+    pub sender: SenderPis,
+    pub transfer_data: TransferData,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delivery_mode: Option<DeliveryMode>,
+    pub system: PaymentSystem,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hold: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution_mode: Option<ExecutionMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub split_payment: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_info_sp: Option<TransactionInfoSp>,
+}
+
+/// Privilege to initiate an EEA payment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EeaTransferPrivilege {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope_usage_limit: Option<ScopeUsageLimit>,
+    pub recipient: RecipientPis,
+    pub sender: SenderPis,
+    pub transfer_data: TransferData,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delivery_mode: Option<DeliveryMode>,
+    pub system: PaymentSystem,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hold: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution_mode: Option<ExecutionMode>,
+}
+
+/// Privilege to initiate a non-EEA payment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NonEeaTransferPrivilege {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope_usage_limit: Option<ScopeUsageLimit>,
+    pub recipient: RecipientPis,
+    pub recipient_bank: Bank,
+    pub sender: SenderPis,
+    pub transfer_data: TransferData,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transfer_charges: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delivery_mode: Option<DeliveryMode>,
+    pub system: PaymentSystem,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hold: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution_mode: Option<ExecutionMode>,
+}
+
+/// Privilege to initiate a tax payment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaxTransferPrivilege {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope_usage_limit: Option<ScopeUsageLimit>,
+    pub recipient: RecipientPis,
+    pub sender: SenderPis,
+    pub transfer_data: TransferData,
+    pub us_info: UsInfo,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delivery_mode: Option<DeliveryMode>,
+    pub system: PaymentSystem,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hold: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution_mode: Option<ExecutionMode>,
+}
+
+impl From<&DomesticPaymentRequest> for DomesticTransferPrivilege {
+    fn from(request: &DomesticPaymentRequest) -> Self {
+        Self {
+            scope_usage_limit: Some(ScopeUsageLimit::Single),
+            recipient: request.recipient.clone(),
+            sender: request.sender.clone(),
+            transfer_data: request.transfer_data.clone(),
+            delivery_mode: request.delivery_mode,
+            system: request.system,
+            hold: request.hold,
+            execution_mode: request.execution_mode,
+            split_payment: request.split_payment,
+            transaction_info_sp: request.transaction_info_sp.clone(),
+        }
+    }
+}
+
+impl From<&EeaPaymentRequest> for EeaTransferPrivilege {
+    fn from(request: &EeaPaymentRequest) -> Self {
+        Self {
+            scope_usage_limit: Some(ScopeUsageLimit::Single),
+            recipient: request.recipient.clone(),
+            sender: request.sender.clone(),
+            transfer_data: request.transfer_data.clone(),
+            delivery_mode: request.delivery_mode,
+            system: request.system,
+            hold: request.hold,
+            execution_mode: request.execution_mode,
+        }
+    }
+}
+
+impl From<&NonEeaPaymentRequest> for NonEeaTransferPrivilege {
+    fn from(request: &NonEeaPaymentRequest) -> Self {
+        Self {
+            scope_usage_limit: Some(ScopeUsageLimit::Single),
+            recipient: request.recipient.clone(),
+            recipient_bank: request.recipient_bank.clone(),
+            sender: request.sender.clone(),
+            transfer_data: request.transfer_data.clone(),
+            transfer_charges: request.transfer_charges.clone(),
+            delivery_mode: request.delivery_mode,
+            system: request.system,
+            hold: request.hold,
+            execution_mode: request.execution_mode,
+        }
+    }
+}
+
+impl From<&TaxPaymentRequest> for TaxTransferPrivilege {
+    fn from(request: &TaxPaymentRequest) -> Self {
+        Self {
+            scope_usage_limit: Some(ScopeUsageLimit::Single),
+            recipient: request.recipient.clone(),
+            sender: request.sender.clone(),
+            transfer_data: request.transfer_data.clone(),
+            us_info: request.us_info.clone(),
+            delivery_mode: request.delivery_mode,
+            system: request.system,
+            hold: request.hold,
+            execution_mode: request.execution_mode,
+        }
+    }
+}
+
+/// Privileges granted for one account (or for the account list)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrivilegeList {
+    #[serde(rename = "accountNumber", skip_serializing_if = "Option::is_none")]
+    pub account_number: Option<String>,
+    #[serde(rename = "ais-accounts:getAccounts", skip_serializing_if = "Option::is_none")]
+    pub get_accounts: Option<SimplePrivilege>,
+    #[serde(rename = "ais:getAccount", skip_serializing_if = "Option::is_none")]
+    pub get_account: Option<SimplePrivilege>,
+    #[serde(rename = "ais:getHolds", skip_serializing_if = "Option::is_none")]
+    pub get_holds: Option<HistoryPrivilege>,
+    #[serde(rename = "ais:getTransactionsDone", skip_serializing_if = "Option::is_none")]
+    pub get_transactions_done: Option<HistoryPrivilege>,
+    #[serde(rename = "ais:getTransactionsPending", skip_serializing_if = "Option::is_none")]
+    pub get_transactions_pending: Option<HistoryPrivilege>,
+    #[serde(rename = "ais:getTransactionsRejected", skip_serializing_if = "Option::is_none")]
+    pub get_transactions_rejected: Option<HistoryPrivilege>,
+    #[serde(rename = "ais:getTransactionsCancelled", skip_serializing_if = "Option::is_none")]
+    pub get_transactions_cancelled: Option<HistoryPrivilege>,
+    #[serde(rename = "ais:getTransactionsScheduled", skip_serializing_if = "Option::is_none")]
+    pub get_transactions_scheduled: Option<HistoryPrivilege>,
+    #[serde(rename = "ais:getTransactionDetail", skip_serializing_if = "Option::is_none")]
+    pub get_transaction_detail: Option<SimplePrivilege>,
+    #[serde(rename = "pis:getPayment", skip_serializing_if = "Option::is_none")]
+    pub get_payment: Option<PaymentPrivilege>,
+    #[serde(rename = "pis:domestic", skip_serializing_if = "Option::is_none")]
+    pub domestic: Option<DomesticTransferPrivilege>,
+    #[serde(rename = "pis:EEA", skip_serializing_if = "Option::is_none")]
+    pub eea: Option<EeaTransferPrivilege>,
+    #[serde(rename = "pis:nonEEA", skip_serializing_if = "Option::is_none")]
+    pub non_eea: Option<NonEeaTransferPrivilege>,
+    #[serde(rename = "pis:tax", skip_serializing_if = "Option::is_none")]
+    pub tax: Option<TaxTransferPrivilege>,
+}
+
+impl PrivilegeList {
+    /// Create an empty privilege list not bound to an account
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty privilege list for the given account
+    pub fn for_account(account_number: impl Into<String>) -> Self {
+        Self {
+            account_number: Some(account_number.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Grant `ais-accounts:getAccounts`
+    pub fn with_get_accounts(mut self, limit: ScopeUsageLimit) -> Self {
+        self.get_accounts = Some(SimplePrivilege { scope_usage_limit: limit });
+        self
+    }
+
+    /// Grant `ais:getAccount`
+    pub fn with_get_account(mut self, limit: ScopeUsageLimit) -> Self {
+        self.get_account = Some(SimplePrivilege { scope_usage_limit: limit });
+        self
+    }
+
+    /// Grant `ais:getTransactionDetail`
+    pub fn with_get_transaction_detail(mut self, limit: ScopeUsageLimit) -> Self {
+        self.get_transaction_detail = Some(SimplePrivilege { scope_usage_limit: limit });
+        self
+    }
+
+    /// Grant `ais:getHolds` for the last `days` days
+    pub fn with_get_holds(mut self, limit: ScopeUsageLimit, days: u32) -> Self {
+        self.get_holds = Some(history(limit, days));
+        self
+    }
+
+    /// Grant `ais:getTransactionsDone` for the last `days` days
+    pub fn with_get_transactions_done(mut self, limit: ScopeUsageLimit, days: u32) -> Self {
+        self.get_transactions_done = Some(history(limit, days));
+        self
+    }
+
+    /// Grant `ais:getTransactionsPending` for the last `days` days
+    pub fn with_get_transactions_pending(mut self, limit: ScopeUsageLimit, days: u32) -> Self {
+        self.get_transactions_pending = Some(history(limit, days));
+        self
+    }
+
+    /// Grant `ais:getTransactionsRejected` for the last `days` days
+    pub fn with_get_transactions_rejected(mut self, limit: ScopeUsageLimit, days: u32) -> Self {
+        self.get_transactions_rejected = Some(history(limit, days));
+        self
+    }
+
+    /// Grant `ais:getTransactionsCancelled` for the last `days` days
+    pub fn with_get_transactions_cancelled(mut self, limit: ScopeUsageLimit, days: u32) -> Self {
+        self.get_transactions_cancelled = Some(history(limit, days));
+        self
+    }
+
+    /// Grant `ais:getTransactionsScheduled` for the last `days` days
+    pub fn with_get_transactions_scheduled(mut self, limit: ScopeUsageLimit, days: u32) -> Self {
+        self.get_transactions_scheduled = Some(history(limit, days));
+        self
+    }
+
+    /// Grant `pis:getPayment`
+    pub fn with_get_payment(mut self, privilege: PaymentPrivilege) -> Self {
+        self.get_payment = Some(privilege);
+        self
+    }
+
+    /// Grant `pis:domestic`
+    pub fn with_domestic(mut self, privilege: impl Into<DomesticTransferPrivilege>) -> Self {
+        self.domestic = Some(privilege.into());
+        self
+    }
+
+    /// Grant `pis:EEA`
+    pub fn with_eea(mut self, privilege: impl Into<EeaTransferPrivilege>) -> Self {
+        self.eea = Some(privilege.into());
+        self
+    }
+
+    /// Grant `pis:nonEEA`
+    pub fn with_non_eea(mut self, privilege: impl Into<NonEeaTransferPrivilege>) -> Self {
+        self.non_eea = Some(privilege.into());
+        self
+    }
+
+    /// Grant `pis:tax`
+    pub fn with_tax(mut self, privilege: impl Into<TaxTransferPrivilege>) -> Self {
+        self.tax = Some(privilege.into());
+        self
+    }
+
+    fn history_privileges(&self) -> [(&'static str, Option<&HistoryPrivilege>); 6] {
+        [
+            ("ais:getHolds", self.get_holds.as_ref()),
+            ("ais:getTransactionsDone", self.get_transactions_done.as_ref()),
+            ("ais:getTransactionsPending", self.get_transactions_pending.as_ref()),
+            ("ais:getTransactionsRejected", self.get_transactions_rejected.as_ref()),
+            ("ais:getTransactionsCancelled", self.get_transactions_cancelled.as_ref()),
+            ("ais:getTransactionsScheduled", self.get_transactions_scheduled.as_ref()),
+        ]
+    }
+
+    fn has_account_privileges(&self) -> bool {
+        self.get_account.is_some()
+            || self.get_transaction_detail.is_some()
+            || self.history_privileges().iter().any(|(_, privilege)| privilege.is_some())
+    }
+
+    fn payment_usage_limits(&self) -> [(&'static str, Option<Option<ScopeUsageLimit>>); 4] {
+        [
+            ("pis:domestic", self.domestic.as_ref().map(|p| p.scope_usage_limit)),
+            ("pis:EEA", self.eea.as_ref().map(|p| p.scope_usage_limit)),
+            ("pis:nonEEA", self.non_eea.as_ref().map(|p| p.scope_usage_limit)),
+            ("pis:tax", self.tax.as_ref().map(|p| p.scope_usage_limit)),
+        ]
+    }
+
+    fn has_payment_privileges(&self) -> bool {
+        self.get_payment.is_some() || self.payment_usage_limits().iter().any(|(_, limit)| limit.is_some())
+    }
+
+    fn validate(&self, group: ScopeGroupType) -> Result<()> {
+        let account_privileges = self.has_account_privileges();
+        let payment_privileges = self.has_payment_privileges();
+
+        if self.get_accounts.is_none() && !account_privileges && !payment_privileges {
+            return Err(invalid("privilege list entry grants no privileges"));
+        }
+        match group {
+            ScopeGroupType::AisAccounts if account_privileges || payment_privileges => {
+                return Err(invalid("scope group ais-accounts only allows ais-accounts:getAccounts"));
+            }
+            ScopeGroupType::Ais if payment_privileges => {
+                return Err(invalid("scope group ais does not allow pis privileges"));
+            }
+            ScopeGroupType::Pis if self.get_accounts.is_some() || account_privileges => {
+                return Err(invalid("scope group pis does not allow ais privileges"));
+            }
+            _ => {}
+        }
+
+        if self.get_accounts.is_some() && self.account_number.is_some() {
+            return Err(invalid("ais-accounts:getAccounts cannot be bound to an account number"));
+        }
+        if account_privileges && self.account_number.as_deref().is_none_or(str::is_empty) {
+            return Err(invalid("ais privileges require an account number"));
+        }
+        for (name, privilege) in self.history_privileges() {
+            if let Some(privilege) = privilege {
+                if !(1..=MAX_HISTORY_DAYS).contains(&privilege.max_allowed_history_long) {
+                    return Err(invalid(format!(
+                        "{} maxAllowedHistoryLong must be between 1 and {} days",
+                        name, MAX_HISTORY_DAYS
+                    )));
+                }
+            }
+        }
+        for (name, limit) in self.payment_usage_limits() {
+            if limit == Some(Some(ScopeUsageLimit::Multiple)) {
+                return Err(invalid(format!("{} can only be granted for single use", name)));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn history(limit: ScopeUsageLimit, days: u32) -> HistoryPrivilege {
+    HistoryPrivilege { scope_usage_limit: limit, max_allowed_history_long: days }
+}
+
+fn invalid(message: impl Into<String>) -> PolishApiError {
+    PolishApiError::Validation(format!("Invalid scope_details: {}", message.into()))
+}
+
+/// Consent requested in (and granted by) the Authorization Service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopeDetails {
+    pub privilege_list: Vec<PrivilegeList>,
+    pub scope_group_type: ScopeGroupType,
+    pub consent_id: String,
+    pub scope_time_limit: DateTime<Utc>,
+    pub throttling_policy: ThrottlingPolicy,
+}
+
+impl ScopeDetails {
+    /// Start building the scope details of a consent
+    pub fn builder(scope_group_type: ScopeGroupType, consent_id: impl Into<String>) -> ScopeDetailsBuilder {
+        ScopeDetailsBuilder {
+            privilege_list: Vec::new(),
+            scope_group_type,
+            consent_id: consent_id.into(),
+            scope_time_limit: None,
+            throttling_policy: ThrottlingPolicy::default(),
+        }
+    }
+
+    /// Check the combination of privileges, scope group and time limit
+    pub fn validate(&self) -> Result<()> {
+        if self.consent_id.is_empty() {
+            return Err(invalid("consentId must not be empty"));
+        }
+        if self.privilege_list.is_empty() {
+            return Err(invalid("privilegeList must not be empty"));
+        }
+        if self.scope_time_limit <= Utc::now() {
+            return Err(invalid("scopeTimeLimit must be in the future"));
+        }
+        for privileges in &self.privilege_list {
+            privileges.validate(self.scope_group_type)?;
+        }
+        Ok(())
+    }
+}
+
+/// Builder for [`ScopeDetails`]
+#[derive(Debug, Clone)]
+pub struct ScopeDetailsBuilder {
+    privilege_list: Vec<PrivilegeList>,
+    scope_group_type: ScopeGroupType,
+    consent_id: String,
+    scope_time_limit: Option<DateTime<Utc>>,
+    throttling_policy: ThrottlingPolicy,
+}
+
+impl ScopeDetailsBuilder {
+    /// Add the privileges for one account
+    pub fn privilege(mut self, privileges: PrivilegeList) -> Self {
+        self.privilege_list.push(privileges);
+        self
+    }
+
+    /// Set the time the consent expires
+    pub fn scope_time_limit(mut self, limit: DateTime<Utc>) -> Self {
+        self.scope_time_limit = Some(limit);
+        self
+    }
+
+    /// Set the consent to expire after `duration` from now
+    pub fn valid_for(self, duration: chrono::Duration) -> Self {
+        self.scope_time_limit(Utc::now() + duration)
+    }
+
+    /// Set the throttling policy
+    pub fn throttling_policy(mut self, policy: ThrottlingPolicy) -> Self {
+        self.throttling_policy = policy;
+        self
+    }
+
+    /// Validate and build the scope details
+    pub fn build(self) -> Result<ScopeDetails> {
+        let scope_time_limit = self
+            .scope_time_limit
+            .ok_or_else(|| invalid("scopeTimeLimit is required"))?;
+        let details = ScopeDetails {
+            privilege_list: self.privilege_list,
+            scope_group_type: self.scope_group_type,
+            consent_id: self.consent_id,
+            scope_time_limit,
+            throttling_policy: self.throttling_policy,
+        };
+        details.validate()?;
+        Ok(details)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::NameAddress;
+    use serde_json::json;
+
+    const ACCOUNT: &str = "PL61109010140000071219812874";
+
+    fn ais() -> ScopeDetailsBuilder {
+        ScopeDetails::builder(ScopeGroupType::Ais, "consent-1").valid_for(chrono::Duration::days(90))
+    }
+
+    fn domestic(limit: ScopeUsageLimit) -> DomesticTransferPrivilege {
+        DomesticTransferPrivilege {
+            scope_usage_limit: Some(limit),
+            recipient: RecipientPis {
+                account_number: "PL27114020040000300201355387".to_string(),
+                name_address: NameAddress { value: vec!["Jan Kowalski".to_string()] },
+            },
+            sender: SenderPis::default(),
+            transfer_data: TransferData {
+                description: "Invoice 1".to_string(),
+                amount: "10.00".to_string(),
+                execution_date: None,
+                currency: "PLN".to_string(),
+            },
+            delivery_mode: None,
+            system: PaymentSystem::Elixir,
+            hold: None,
+            execution_mode: None,
+            split_payment: None,
+            transaction_info_sp: None,
+        }
+    }
+
+    #[test]
+    fn test_ais_scope_serialization() {
+        let details = ais()
+            .privilege(
+                PrivilegeList::for_account(ACCOUNT)
+                    .with_get_account(ScopeUsageLimit::Multiple)
+                    .with_get_transactions_done(ScopeUsageLimit::Multiple, 90),
+            )
+            .build()
+            .unwrap();
+
+        let value = serde_json::to_value(&details).unwrap();
+        assert_eq!(value["scopeGroupType"], "ais");
+        assert_eq!(value["throttlingPolicy"], "psd2Regulatory");
+        assert_eq!(
+            value["privilegeList"],
+            json!([{
+                "accountNumber": ACCOUNT,
+                "ais:getAccount": { "scopeUsageLimit": "multiple" },
+                "ais:getTransactionsDone": { "scopeUsageLimit": "multiple", "maxAllowedHistoryLong": 90 }
+            }])
+        );
+    }
+
+    #[test]
+    fn test_pis_scope_serialization() {
+        let details = ScopeDetails::builder(ScopeGroupType::Pis, "consent-2")
+            .valid_for(chrono::Duration::hours(1))
+            .privilege(PrivilegeList::new().with_domestic(domestic(ScopeUsageLimit::Single)))
+            .build()
+            .unwrap();
+
+        let value = serde_json::to_value(&details).unwrap();
+        assert_eq!(value["privilegeList"][0]["pis:domestic"]["scopeUsageLimit"], "single");
+        assert_eq!(value["privilegeList"][0]["pis:domestic"]["transferData"]["amount"], "10.00");
+    }
+
+    #[test]
+    fn test_invalid_combinations_are_rejected() {
+        let cases = [
+            // No privileges at all
+            ais().build(),
+            ais().privilege(PrivilegeList::for_account(ACCOUNT)).build(),
+            // Missing time limit
+            ScopeDetails::builder(ScopeGroupType::Ais, "consent-1")
+                .privilege(PrivilegeList::for_account(ACCOUNT).with_get_account(ScopeUsageLimit::Single))
+                .build(),
+            // Expired time limit
+            ais()
+                .scope_time_limit(Utc::now() - chrono::Duration::days(1))
+                .privilege(PrivilegeList::for_account(ACCOUNT).with_get_account(ScopeUsageLimit::Single))
+                .build(),
+            // Account privileges without an account number
+            ais().privilege(PrivilegeList::new().with_get_account(ScopeUsageLimit::Single)).build(),
+            // History out of range
+            ais()
+                .privilege(PrivilegeList::for_account(ACCOUNT).with_get_holds(ScopeUsageLimit::Single, 0))
+                .build(),
+            ais()
+                .privilege(PrivilegeList::for_account(ACCOUNT).with_get_transactions_done(ScopeUsageLimit::Single, 5000))
+                .build(),
+            // Privileges outside the scope group
+            ScopeDetails::builder(ScopeGroupType::AisAccounts, "consent-1")
+                .valid_for(chrono::Duration::days(1))
+                .privilege(PrivilegeList::for_account(ACCOUNT).with_get_account(ScopeUsageLimit::Single))
+                .build(),
+            ais().privilege(PrivilegeList::new().with_domestic(domestic(ScopeUsageLimit::Single))).build(),
+            ScopeDetails::builder(ScopeGroupType::Pis, "consent-1")
+                .valid_for(chrono::Duration::days(1))
+                .privilege(PrivilegeList::new().with_get_accounts(ScopeUsageLimit::Single))
+                .build(),
+            // getAccounts bound to an account
+            ais().privilege(PrivilegeList::for_account(ACCOUNT).with_get_accounts(ScopeUsageLimit::Single)).build(),
+            // Payments are single use
+            ScopeDetails::builder(ScopeGroupType::Pis, "consent-1")
+                .valid_for(chrono::Duration::days(1))
+                .privilege(PrivilegeList::new().with_domestic(domestic(ScopeUsageLimit::Multiple)))
+                .build(),
+            // Empty consent id
+            ScopeDetails::builder(ScopeGroupType::AisAccounts, "")
+                .valid_for(chrono::Duration::days(1))
+                .privilege(PrivilegeList::new().with_get_accounts(ScopeUsageLimit::Single))
+                .build(),
+        ];
+
+        for (index, result) in cases.into_iter().enumerate() {
+            assert!(matches!(result, Err(PolishApiError::Validation(_))), "case {} was accepted", index);
+        }
+    }
+
+    #[test]
+    fn test_accounts_scope() {
+        let details = ScopeDetails::builder(ScopeGroupType::AisAccounts, "consent-3")
+            .valid_for(chrono::Duration::days(1))
+            .privilege(PrivilegeList::new().with_get_accounts(ScopeUsageLimit::Multiple))
+            .build()
+            .unwrap();
+        assert_eq!(details.scope_group_type.as_str(), "ais-accounts");
+    }
+}
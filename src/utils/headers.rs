@@ -1,5 +1,5 @@
 use uuid::Uuid;
-use crate::types::{RequestHeaders, PolishApiError, Result};
+use crate::types::{PsuContext, RequestHeaders, PolishApiError, Result};
 
 /// Builder for creating request headers
 pub struct HeadersBuilder {
@@ -32,6 +32,48 @@ impl HeadersBuilder {
         self
     }
 
+    /// Set the PSU context forwarded in the `requestHeader`
+    pub fn psu_context(mut self, psu: PsuContext) -> Self {
+        self.headers.psu = psu;
+        self
+    }
+
+    /// Set the PSU's IP address
+    pub fn psu_ip_address(mut self, ip_address: impl Into<String>) -> Self {
+        self.headers.psu.ip_address = Some(ip_address.into());
+        self
+    }
+
+    /// Set the user agent of the PSU's browser or application
+    pub fn psu_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.headers.psu.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Mark AIS calls as made with (`true`) or without (`false`) the PSU taking part
+    pub fn direct_psu(mut self, is_direct_psu: bool) -> Self {
+        self.headers.psu.is_direct_psu = Some(is_direct_psu);
+        self
+    }
+
+    /// Set the callback URL for asynchronous notifications
+    pub fn callback_url(mut self, callback_url: impl Into<String>) -> Self {
+        self.headers.callback_url = Some(callback_url.into());
+        self
+    }
+
+    /// Set the API key the ASPSP sends back with callback notifications
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.headers.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Set the consent the call is made under, counting it against that consent's quotas
+    pub fn consent_id(mut self, consent_id: impl Into<String>) -> Self {
+        self.headers.consent_id = Some(consent_id.into());
+        self
+    }
+
     /// Build the headers
     pub fn build(self) -> RequestHeaders {
         self.headers
@@ -92,6 +134,20 @@ mod tests {
         assert_eq!(headers.accept_charset, "utf-8");
     }
 
+    #[test]
+    fn test_headers_builder_psu_context() {
+        let headers = HeadersBuilder::new()
+            .psu_ip_address("192.0.2.1")
+            .psu_user_agent("Mozilla/5.0")
+            .callback_url("https://tpp.example.com/callback")
+            .build();
+
+        assert_eq!(headers.psu.ip_address.as_deref(), Some("192.0.2.1"));
+        assert_eq!(headers.psu.user_agent.as_deref(), Some("Mozilla/5.0"));
+        assert_eq!(headers.callback_url.as_deref(), Some("https://tpp.example.com/callback"));
+        assert!(headers.api_key.is_none());
+    }
+
     #[test]
     fn test_validate_authorization_header() {
         assert!(validate_authorization_header("Bearer valid-token").is_ok());